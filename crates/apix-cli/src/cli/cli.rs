@@ -1,12 +1,31 @@
+use apix_core::plugin::scaffold::PluginLang;
 use clap::{Args, Parser, Subcommand, command};
 use log::error;
+use std::path::PathBuf;
 
 use crate::{
     cli::{
-        commands::{init::create_monorepo, install::install_plugin, update::update_plugin},
+        commands::{
+            adopt::adopt_repository,
+            doctor::run_doctor,
+            init::create_monorepo,
+            install::install_plugin,
+            plugin_dev::{
+                check::check_plugin_dir, link::link_plugin_dir, new::new_plugin, test::test_plugin,
+                types::write_types,
+            },
+            plugins::{list::list_plugins, search::search_plugins},
+            run::run_recipe,
+            status::show_status,
+            template::{
+                add::add_template, default::set_default_template, list::list_templates,
+                remove::remove_template,
+            },
+            update::update_plugin,
+        },
         prelude::pre_command_checks,
     },
-    plugin::{dispatcher::dispatch_plugin_command, helpers::resolve_plugin},
+    plugin::dispatcher::dispatch_plugin_command,
 };
 
 #[derive(Parser)]
@@ -20,10 +39,42 @@ struct CLI {
 #[derive(Subcommand)]
 pub enum Commands {
     Init {
+        #[arg(help = "Name of the monorepo folder, or '.' for the current folder")]
         name: String,
 
-        #[arg(short, long)]
+        #[arg(
+            short,
+            long,
+            help = "Installed template name, or a path like ./my-template or a .tar/.tar.gz archive"
+        )]
         template: Option<String>,
+
+        #[arg(
+            long = "set",
+            value_name = "KEY=VALUE",
+            value_parser = parse_key_value,
+            help = "Set a template variable without prompting"
+        )]
+        set: Vec<(String, String)>,
+
+        #[arg(
+            short = 'y',
+            long = "yes",
+            help = "Use defaults for template variables not given with --set"
+        )]
+        accept_defaults: bool,
+
+        #[arg(long, help = "Initialize into an existing empty folder")]
+        force: bool,
+    },
+    /// Bring an existing repository under apix management
+    Adopt {
+        #[arg(
+            short = 'y',
+            long = "yes",
+            help = "Register the detected layout without reviewing it"
+        )]
+        accept_all: bool,
     },
     Install {
         plugin: Option<String>,
@@ -38,6 +89,120 @@ pub enum Commands {
         #[command(subcommand)]
         command: PluginCommands,
     },
+    /// Run a recipe from monorepo.toml or a recipe file as a single plan
+    Run {
+        #[arg(help = "Name of a [recipes] entry in monorepo.toml, or a recipe file")]
+        recipe: String,
+
+        #[arg(
+            long = "set",
+            value_name = "KEY=VALUE",
+            value_parser = parse_key_value,
+            help = "Set a recipe variable used as {{KEY}} in step arguments"
+        )]
+        set: Vec<(String, String)>,
+
+        #[command(flatten)]
+        flags: CommonFlags,
+    },
+    /// Check the apix installation and the current monorepo for problems
+    Doctor,
+    /// Show the monorepo's projects, packages and plugins
+    Status {
+        #[arg(long, help = "Print the result as JSON")]
+        json: bool,
+    },
+    /// Tools for writing plugins
+    PluginDev {
+        #[command(subcommand)]
+        command: PluginDevCommands,
+    },
+    /// Inspect installed plugins
+    Plugins {
+        #[command(subcommand)]
+        command: PluginsCommands,
+    },
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PluginDevCommands {
+    /// Scaffold a new plugin in a folder named after it
+    New {
+        name: String,
+        #[arg(
+            long,
+            default_value = "lua",
+            value_parser = PluginLang::parse,
+            help = "Language of the entry file: lua or luau"
+        )]
+        lang: PluginLang,
+    },
+    /// Symlink a plugin working copy into ~/.apix/plugins/<name>/<version>
+    Link { path: PathBuf },
+    /// Run the plugin's test cases and compare them with their snapshots
+    Test {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        #[arg(long, help = "Only run the test case with this name")]
+        case: Option<String>,
+        #[arg(long, help = "Rewrite snapshots with the current output")]
+        update: bool,
+    },
+    /// Type-check the plugin's entry file against the ctx API without running it
+    Check {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Print luau-lsp definitions for the ctx API
+    Types {
+        #[arg(
+            short,
+            long,
+            help = "Write to this file, or to apix.d.luau in this folder"
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PluginsCommands {
+    /// List installed plugins and their versions
+    List {
+        #[arg(long, help = "Print the result as JSON")]
+        json: bool,
+    },
+    /// Search installed plugins by name, description, language or feature
+    Search {
+        term: Option<String>,
+        #[arg(long, help = "Only plugins supporting this language")]
+        language: Option<String>,
+        #[arg(long, help = "Only plugins providing this feature")]
+        feature: Option<String>,
+        #[arg(long, help = "Print the result as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// Install a template from a directory or .tar/.tar.gz archive
+    Add {
+        source: String,
+        #[arg(long, help = "Name to register the template under")]
+        name: Option<String>,
+        #[arg(long, help = "Make this the default template for 'apix init'")]
+        default: bool,
+    },
+    /// List installed templates
+    List,
+    /// Remove an installed template
+    Remove { name: String },
+    /// Make an installed template the default for 'apix init'
+    Default { name: String },
 }
 
 #[derive(Args)]
@@ -53,6 +218,19 @@ pub struct CommonFlags {
     pub allow_dirty: bool,
 }
 
+#[derive(Args)]
+pub struct TargetFlags {
+    #[arg(
+        long,
+        conflicts_with = "package",
+        help = "Run against a project registered in monorepo.toml"
+    )]
+    pub project: Option<String>,
+
+    #[arg(long, help = "Run against a package registered in monorepo.toml")]
+    pub package: Option<String>,
+}
+
 #[derive(Subcommand)]
 pub enum PluginCommands {
     Create {
@@ -60,17 +238,51 @@ pub enum PluginCommands {
         #[command(flatten)]
         flags: CommonFlags,
     },
+    // `--help` is passed on with the arguments and answered from the plugin's info.
+    // apix flags after the first argument are split off by `take_apix_flags`.
+    #[command(disable_help_flag = true)]
     Extend {
-        #[arg(trailing_var_arg = true)]
+        #[arg(allow_hyphen_values = true)]
         args: Vec<String>,
+        /// Arguments after `--`, passed on unchanged.
+        #[arg(last = true, hide = true)]
+        escaped: Vec<String>,
+        #[arg(long, help = "Re-apply even if the same extend was already applied")]
+        force: bool,
+        #[command(flatten)]
+        target: TargetFlags,
         #[command(flatten)]
         flags: CommonFlags,
     },
     Migrate {
+        #[arg(
+            long,
+            conflicts_with_all = ["project", "package"],
+            help = "Migrate every project and package owned by the plugin"
+        )]
+        all: bool,
+        #[arg(
+            long,
+            value_name = "VERSION",
+            help = "Installed version to migrate to, the newest by default"
+        )]
+        to: Option<String>,
+        #[command(flatten)]
+        target: TargetFlags,
         #[command(flatten)]
         flags: CommonFlags,
     },
     Info,
+    /// A command declared under [commands] in the plugin's plugin.toml
+    #[command(external_subcommand)]
+    Custom(Vec<String>),
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", value))
 }
 
 pub fn init_cli() {
@@ -82,12 +294,45 @@ pub fn init_cli() {
     }
 
     match cli.command {
-        Commands::Init { name, template } => create_monorepo(name, template),
+        Commands::Init {
+            name,
+            template,
+            set,
+            accept_defaults,
+            force,
+        } => create_monorepo(name, template, set, accept_defaults, force),
+        Commands::Adopt { accept_all } => adopt_repository(accept_all),
         Commands::Install { plugin, version } => install_plugin(plugin, version),
         Commands::Update { plugin, version } => update_plugin(plugin, version),
-        Commands::Plugin { plugin, command } => {
-            let (plugin_config, abi, ctx) = resolve_plugin(&plugin);
-            dispatch_plugin_command(plugin, plugin_config, abi, ctx, command);
-        }
+        Commands::Plugin { plugin, command } => dispatch_plugin_command(plugin, command),
+        Commands::Run { recipe, set, flags } => run_recipe(recipe, set, flags),
+        Commands::Doctor => run_doctor(),
+        Commands::Status { json } => show_status(json),
+        Commands::PluginDev { command } => match command {
+            PluginDevCommands::New { name, lang } => new_plugin(name, lang),
+            PluginDevCommands::Link { path } => link_plugin_dir(path),
+            PluginDevCommands::Test { path, case, update } => test_plugin(path, case, update),
+            PluginDevCommands::Check { path } => check_plugin_dir(path),
+            PluginDevCommands::Types { output } => write_types(output),
+        },
+        Commands::Plugins { command } => match command {
+            PluginsCommands::List { json } => list_plugins(json),
+            PluginsCommands::Search {
+                term,
+                language,
+                feature,
+                json,
+            } => search_plugins(term, language, feature, json),
+        },
+        Commands::Template { command } => match command {
+            TemplateCommands::Add {
+                source,
+                name,
+                default,
+            } => add_template(source, name, default),
+            TemplateCommands::List => list_templates(),
+            TemplateCommands::Remove { name } => remove_template(name),
+            TemplateCommands::Default { name } => set_default_template(name),
+        },
     }
 }
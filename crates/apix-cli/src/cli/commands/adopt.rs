@@ -0,0 +1,197 @@
+use std::{env, fs, path::Path};
+
+use ansi_term::Colour::{Cyan, Green};
+use apix_core::{
+    db::Db,
+    monorepo::{
+        adopt::{DetectedUnit, detect_units, render_monorepo_config},
+        config::UnitKind,
+    },
+};
+use log::{error, info};
+
+use crate::utils::prompt::{ask, confirm};
+
+pub fn adopt_repository(accept_all: bool) {
+    let monorepo_root = env::current_dir().unwrap_or_else(|e| {
+        error!("Failed to get current folder: {}", e);
+        std::process::exit(1);
+    });
+
+    if monorepo_root.join("monorepo.toml").exists() {
+        error!("This repository is already managed by apix");
+        std::process::exit(1);
+    }
+
+    let mut units = detect_units(&monorepo_root).unwrap_or_else(|e| {
+        error!("Failed to scan repository: {}", e);
+        std::process::exit(1);
+    });
+
+    if units.is_empty() {
+        info!("No projects or packages detected, registering an empty monorepo");
+    } else if !accept_all {
+        review_units(&mut units);
+    } else {
+        print_units(&units);
+    }
+
+    let repo_name = monorepo_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let config = loop {
+        match render_monorepo_config(&monorepo_root, &repo_name, &units) {
+            Ok(config) => break config,
+            Err(e) if !accept_all => {
+                error!("Reviewed layout is not a valid monorepo: {}", e);
+                review_units(&mut units);
+            }
+            Err(e) => {
+                error!("Detected layout is not a valid monorepo: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    if !accept_all && !confirm("Write monorepo.toml and .apix/state.db?") {
+        info!("Adoption cancelled");
+        return;
+    }
+
+    if let Err(e) = write_monorepo(&monorepo_root, &config) {
+        error!("Failed to adopt repository: {}", e);
+        std::process::exit(1);
+    }
+
+    info!(
+        "Adopted '{}' with {} project(s) and {} package(s)",
+        repo_name,
+        units.iter().filter(|u| u.kind == UnitKind::Project).count(),
+        units.iter().filter(|u| u.kind == UnitKind::Package).count()
+    );
+}
+
+fn print_units(units: &[DetectedUnit]) {
+    println!("\nDetected layout:");
+    for (i, unit) in units.iter().enumerate() {
+        let kind = match unit.kind {
+            UnitKind::Project => Green.paint("project"),
+            UnitKind::Package => Cyan.paint("package"),
+        };
+        println!(
+            "  {:>2}. {} {:<20} {:<30} {} ({})",
+            i + 1,
+            kind,
+            unit.name,
+            unit.path,
+            unit.language,
+            unit.marker
+        );
+    }
+    println!();
+}
+
+/// Lets the user rename, reclassify or drop detected units until they accept the list.
+fn review_units(units: &mut Vec<DetectedUnit>) {
+    loop {
+        print_units(units);
+
+        let answer = ask("Number to edit, -number to drop, Enter to accept", None);
+        if answer.is_empty() {
+            return;
+        }
+
+        let (drop, number) = match answer.strip_prefix('-') {
+            Some(number) => (true, number),
+            None => (false, answer.as_str()),
+        };
+
+        let index = match number.parse::<usize>() {
+            Ok(n) if (1..=units.len()).contains(&n) => n - 1,
+            _ => {
+                error!("Expected a number between 1 and {}", units.len());
+                continue;
+            }
+        };
+
+        if drop {
+            units.remove(index);
+        } else {
+            edit_unit(&mut units[index]);
+        }
+    }
+}
+
+fn edit_unit(unit: &mut DetectedUnit) {
+    unit.name = ask("Name", Some(&unit.name));
+
+    loop {
+        match ask("Kind (project/package)", Some(unit.kind.as_str())).as_str() {
+            "project" => unit.kind = UnitKind::Project,
+            "package" => unit.kind = UnitKind::Package,
+            other => {
+                error!("Unknown kind '{}'", other);
+                continue;
+            }
+        }
+        break;
+    }
+
+    unit.language = ask("Language", Some(&unit.language));
+}
+
+/// Creates the state DB first so that a failure leaves no `monorepo.toml` behind.
+fn write_monorepo(monorepo_root: &Path, config: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let apix_dir = monorepo_root.join(".apix");
+    let db_path = apix_dir.join("state.db");
+    let created_apix_dir = !apix_dir.exists();
+    let created_db = !db_path.exists();
+
+    let result = Db::create_db_and_migrate(&db_path)
+        .map_err(|e| format!("Failed to create and migrate DB: {}", e).into())
+        .and_then(|_| {
+            let tmp_path = monorepo_root.join("monorepo.toml.tmp");
+            fs::write(&tmp_path, config)?;
+            fs::rename(&tmp_path, monorepo_root.join("monorepo.toml"))?;
+            Ok(())
+        });
+
+    let result = result.and_then(|_| ignore_state_dir(monorepo_root));
+
+    // Only what this run created is removed, an existing DB keeps its history.
+    if result.is_err() {
+        if created_apix_dir {
+            let _ = fs::remove_dir_all(&apix_dir);
+        } else if created_db {
+            let _ = fs::remove_file(&db_path);
+        }
+        let _ = fs::remove_file(monorepo_root.join("monorepo.toml.tmp"));
+        let _ = fs::remove_file(monorepo_root.join("monorepo.toml"));
+    }
+
+    result
+}
+
+/// Keeps the local state DB out of version control, like the bundled templates do.
+fn ignore_state_dir(monorepo_root: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let gitignore = monorepo_root.join(".gitignore");
+    let content = fs::read_to_string(&gitignore).unwrap_or_default();
+
+    if content
+        .lines()
+        .any(|line| matches!(line.trim(), ".apix" | ".apix/" | "/.apix" | "/.apix/"))
+    {
+        return Ok(());
+    }
+
+    let separator = if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    fs::write(&gitignore, format!("{}{}/.apix/\n", content, separator))?;
+
+    Ok(())
+}
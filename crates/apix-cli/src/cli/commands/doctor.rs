@@ -0,0 +1,328 @@
+use std::path::Path;
+
+use ansi_term::Colour::{Green, Red, Yellow};
+use apix_core::{
+    db::Db,
+    monorepo::config::{MonorepoConfig, get_monorepo_config},
+    plugin::{config::get_plugin_config, tools::check_tool, utils::load_plugin},
+    template::meta::TemplateMeta,
+};
+
+use crate::utils::internal_dir::get_internal_dir;
+
+#[derive(PartialEq, Eq)]
+enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+struct Check {
+    severity: Severity,
+    message: String,
+    fix: Option<String>,
+}
+
+#[derive(Default)]
+struct Report {
+    checks: Vec<Check>,
+}
+
+impl Report {
+    fn ok(&mut self, message: impl Into<String>) {
+        self.push(Severity::Ok, message, None::<String>);
+    }
+
+    fn warn(&mut self, message: impl Into<String>, fix: impl Into<String>) {
+        self.push(Severity::Warning, message, Some(fix));
+    }
+
+    fn error(&mut self, message: impl Into<String>, fix: impl Into<String>) {
+        self.push(Severity::Error, message, Some(fix));
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        message: impl Into<String>,
+        fix: Option<impl Into<String>>,
+    ) {
+        self.checks.push(Check {
+            severity,
+            message: message.into(),
+            fix: fix.map(Into::into),
+        });
+    }
+
+    fn has_errors(&self) -> bool {
+        self.checks.iter().any(|c| c.severity == Severity::Error)
+    }
+}
+
+pub fn run_doctor() {
+    let mut report = Report::default();
+
+    check_internal_dir(&mut report);
+    check_templates(&mut report);
+
+    let monorepo_root = std::env::current_dir().unwrap();
+    if monorepo_root.join("monorepo.toml").exists() {
+        check_state_db(&mut report, &monorepo_root);
+        if let Some(config) = check_monorepo_config(&mut report, &monorepo_root) {
+            check_plugins(&mut report, &monorepo_root, &config);
+        }
+    } else {
+        report.ok("Not inside a monorepo, skipping repository checks");
+    }
+
+    for check in &report.checks {
+        let marker = match check.severity {
+            Severity::Ok => Green.paint("ok   "),
+            Severity::Warning => Yellow.paint("warn "),
+            Severity::Error => Red.paint("error"),
+        };
+        println!("{} {}", marker, check.message);
+        if let Some(fix) = &check.fix {
+            println!("      fix: {}", fix);
+        }
+    }
+
+    if report.has_errors() {
+        std::process::exit(1);
+    }
+}
+
+fn check_internal_dir(report: &mut Report) {
+    let internal_dir = get_internal_dir();
+
+    for (name, dir) in [
+        ("templates", internal_dir.get_templates_dir()),
+        ("migrations", internal_dir.get_migrations_dir()),
+        ("plugins", internal_dir.get_plugins_dir()),
+    ] {
+        if !dir.is_dir() {
+            report.error(
+                format!("{} is missing", dir.display()),
+                format!("mkdir -p {}", dir.display()),
+            );
+            continue;
+        }
+
+        match tempfile::tempfile_in(dir) {
+            Ok(_) => report.ok(format!("~/.apix/{} is writable", name)),
+            Err(e) => report.error(
+                format!("{} is not writable: {}", dir.display(), e),
+                format!("chmod u+rwx {}", dir.display()),
+            ),
+        }
+    }
+}
+
+fn check_templates(report: &mut Report) {
+    let internal_dir = get_internal_dir();
+    let templates_dir = internal_dir.get_templates_dir();
+
+    let meta = match TemplateMeta::load(templates_dir) {
+        Ok(meta) => meta,
+        Err(e) => {
+            report.error(
+                format!("Template meta.yml is invalid: {}", e),
+                format!(
+                    "fix {} or delete it to restore the bundled templates",
+                    templates_dir.join("meta.yml").display()
+                ),
+            );
+            return;
+        }
+    };
+
+    let missing: Vec<_> = meta
+        .templates
+        .iter()
+        .filter(|t| !templates_dir.join(t).is_dir())
+        .collect();
+
+    if missing.is_empty() {
+        report.ok(format!(
+            "meta.yml lists {} template(s), default '{}'",
+            meta.templates.len(),
+            meta.default
+        ));
+    }
+
+    for template in missing {
+        report.error(
+            format!(
+                "Template '{}' is listed in meta.yml but has no folder",
+                template
+            ),
+            format!("apix template remove {}", template),
+        );
+    }
+}
+
+fn check_state_db(report: &mut Report, monorepo_root: &Path) {
+    let db_path = monorepo_root.join(".apix").join("state.db");
+
+    if !db_path.exists() {
+        report.warn(
+            "state.db does not exist yet",
+            "run 'apix status' to create and migrate it",
+        );
+        return;
+    }
+
+    let versions = Db::new(&db_path.to_string_lossy()).and_then(|db| {
+        let current = db.schema_version()?;
+        Ok((current, Db::latest_schema_version()?))
+    });
+
+    match versions {
+        Ok((current, latest)) if current == latest => {
+            report.ok(format!("state.db is at schema version {}", current))
+        }
+        Ok((current, latest)) if current < latest => report.warn(
+            format!(
+                "state.db is at schema version {}, latest is {}",
+                current, latest
+            ),
+            "run 'apix status' to apply pending migrations",
+        ),
+        Ok((current, latest)) => report.error(
+            format!(
+                "state.db is at schema version {}, newer than this apix ({})",
+                current, latest
+            ),
+            "update apix",
+        ),
+        Err(e) => report.error(
+            format!("state.db cannot be opened: {}", e),
+            format!("move {} away and run 'apix status'", db_path.display()),
+        ),
+    }
+}
+
+fn check_monorepo_config(report: &mut Report, monorepo_root: &Path) -> Option<MonorepoConfig> {
+    let config = match get_monorepo_config(&monorepo_root.to_path_buf()) {
+        Ok(config) => config,
+        Err(e) => {
+            report.error(
+                format!("monorepo.toml cannot be parsed: {}", e),
+                "fix the syntax error in monorepo.toml",
+            );
+            return None;
+        }
+    };
+
+    match config.validate() {
+        Ok(_) => report.ok("monorepo.toml is valid"),
+        Err(e) => report.error(
+            format!("monorepo.toml is invalid: {}", e),
+            "edit monorepo.toml",
+        ),
+    }
+
+    let units = config
+        .projects
+        .iter()
+        .map(|(name, p)| ("project", name, &p.path))
+        .chain(
+            config
+                .packages
+                .iter()
+                .map(|(name, p)| ("package", name, &p.path)),
+        );
+
+    for (kind, name, path) in units {
+        if !monorepo_root.join(path).is_dir() {
+            report.error(
+                format!("{} '{}' points to missing folder '{}'", kind, name, path),
+                format!("create '{}' or remove '{}' from monorepo.toml", path, name),
+            );
+        }
+    }
+
+    for (name, other) in config.overlapping_paths() {
+        report.error(
+            format!("'{}' and '{}' have overlapping paths", name, other),
+            "move one of them or merge them into a single entry",
+        );
+    }
+
+    Some(config)
+}
+
+fn check_plugins(report: &mut Report, monorepo_root: &Path, config: &MonorepoConfig) {
+    let internal_dir = get_internal_dir();
+    let plugins_dir = internal_dir.get_plugins_dir();
+
+    let mut plugins: Vec<_> = config.plugins.iter().collect();
+    plugins.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, meta) in plugins {
+        let required = meta.version();
+
+        let (plugin_config, resolved) = match get_plugin_config(plugins_dir, name, required) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                report.error(
+                    format!("Plugin '{}' does not resolve: {}", name, e),
+                    format!("apix install {} {}", name, required),
+                );
+                continue;
+            }
+        };
+
+        if required != "*" && resolved != required {
+            report.warn(
+                format!(
+                    "Plugin '{}' requires {} but resolves to {}",
+                    name, required, resolved
+                ),
+                format!("apix install {} {}", name, required),
+            );
+        }
+
+        let mut tools_ok = true;
+        for (tool, requirement) in &plugin_config.requires.tools {
+            match check_tool(tool, requirement) {
+                Ok(version) => report.ok(format!(
+                    "Plugin '{}' tool '{}' {} satisfies '{}'",
+                    name,
+                    tool,
+                    version,
+                    requirement.version()
+                )),
+                Err(e) => {
+                    tools_ok = false;
+                    report.error(
+                        format!("Plugin '{}': {}", name, e),
+                        format!(
+                            "install or upgrade '{}' to a version matching '{}'",
+                            tool,
+                            requirement.version()
+                        ),
+                    );
+                }
+            }
+        }
+
+        if !tools_ok {
+            continue;
+        }
+
+        match load_plugin(
+            name,
+            &plugin_config.version,
+            &monorepo_root.to_path_buf(),
+            plugins_dir,
+            None,
+        ) {
+            Ok(_) => report.ok(format!("Plugin '{}' {} loads", name, resolved)),
+            Err(e) => report.error(
+                format!("Plugin '{}' {} fails to load: {}", name, resolved, e),
+                format!("reinstall it with 'apix install {} {}'", name, required),
+            ),
+        }
+    }
+}
@@ -1,17 +1,29 @@
-use apix_core::{db::Db, utils};
-use log::{error, info};
-use serde::Deserialize;
-use std::{env, fs, path::Path};
+use apix_core::{
+    db::Db,
+    monorepo::{config::validate_repo_name, editor::MonorepoConfigEditor},
+    template::{
+        manifest::{PostInit, TemplateManifest},
+        render::render_template,
+        source::{TemplateSource, resolve_template},
+    },
+};
+use log::{error, info, warn};
+use std::{collections::HashMap, env, fs, path::Path};
+use tempfile::TempDir;
 
-use crate::utils::internal_dir::get_internal_dir;
+use crate::utils::{
+    git::{commit_all, init_repo},
+    internal_dir::get_internal_dir,
+    prompt::ask,
+};
 
-#[derive(Debug, Deserialize)]
-struct MetaYaml {
-    templates: Vec<String>,
-    default: String,
-}
-
-pub fn create_monorepo(name: String, provided_template: Option<String>) {
+pub fn create_monorepo(
+    name: String,
+    provided_template: Option<String>,
+    set: Vec<(String, String)>,
+    accept_defaults: bool,
+    force: bool,
+) {
     let current_dir = match env::current_dir() {
         Ok(dir) => dir,
         Err(e) => {
@@ -19,19 +31,25 @@ pub fn create_monorepo(name: String, provided_template: Option<String>) {
             return;
         }
     };
-    let dst_path = current_dir.join(&name);
 
-    if dst_path.exists() {
-        error!("Folder '{}' already exists", name);
+    let adopt_current = name == ".";
+    let (dst_path, repo_name) = if adopt_current {
+        let dir_name = current_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        (current_dir.clone(), dir_name)
+    } else {
+        (current_dir.join(&name), name.clone())
+    };
+
+    if let Err(e) = validate_repo_name(&repo_name) {
+        error!("{}", e);
         return;
     }
 
-    if let Err(e) = fs::create_dir_all(&dst_path) {
-        error!(
-            "Failed to create monorepo folder '{}': {}",
-            dst_path.display(),
-            e
-        );
+    if let Err(e) = check_destination(&dst_path, adopt_current, force) {
+        error!("{}", e);
         return;
     }
 
@@ -41,31 +59,58 @@ pub fn create_monorepo(name: String, provided_template: Option<String>) {
         "default".to_string()
     };
 
-    let result = create_monorepo_from_template(&dst_path, &provided_template);
+    // Everything is built in a staging folder next to (or inside) the
+    // destination, so it can be moved into place with a rename. Dropping
+    // the TempDir on any error removes the partial monorepo.
+    let staging_parent = if dst_path.exists() {
+        dst_path.as_path()
+    } else {
+        dst_path.parent().unwrap_or(&current_dir)
+    };
+    let staging = match tempfile::Builder::new()
+        .prefix(".apix-init-")
+        .tempdir_in(staging_parent)
+    {
+        Ok(dir) => dir,
+        Err(e) => {
+            error!("Failed to create staging folder: {}", e);
+            return;
+        }
+    };
+
+    let skip_git = dst_path.join(".git").exists();
+    let result = build_monorepo(
+        staging.path(),
+        &repo_name,
+        &provided_template,
+        set.into_iter().collect(),
+        accept_defaults,
+        skip_git,
+    );
 
     let template = match result {
-        Ok(tp) => tp,
+        Ok(template) => template,
         Err(e) => {
             error!(
                 "Failed to create monorepo from template '{}': {}",
                 provided_template, e
             );
-            fs::remove_dir(dst_path).expect("Failed to remove monorepo folder");
             return;
         }
     };
 
-    let apix_dir = dst_path.join(".apix");
-    let db_path = apix_dir.join("state.db");
-
-    if let Err(e) = Db::create_db_and_migrate(Path::new(&db_path)) {
-        error!("Failed to create and migrate DB: {}", e);
+    if let Err(e) = move_into_place(staging, &dst_path) {
+        error!(
+            "Failed to move monorepo into '{}': {}",
+            dst_path.display(),
+            e
+        );
         return;
-    };
+    }
 
     info!(
         "Successfully created monorepo '{}' with {}template '{}'",
-        name,
+        repo_name,
         if provided_template == "default" {
             "default "
         } else {
@@ -75,41 +120,180 @@ pub fn create_monorepo(name: String, provided_template: Option<String>) {
     );
 }
 
+fn check_destination(dst_path: &Path, adopt_current: bool, force: bool) -> Result<(), String> {
+    if !dst_path.exists() {
+        return Ok(());
+    }
+
+    if dst_path.join("monorepo.toml").exists() {
+        return Err(format!(
+            "'{}' is already managed by apix",
+            dst_path.display()
+        ));
+    }
+
+    if adopt_current {
+        return Ok(());
+    }
+
+    let is_empty = fs::read_dir(dst_path)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false);
+
+    match (force, is_empty) {
+        (true, true) => Ok(()),
+        (true, false) => Err(format!("Folder '{}' is not empty", dst_path.display())),
+        (false, _) => Err(format!(
+            "Folder '{}' already exists, use --force to initialize an existing empty folder",
+            dst_path.display()
+        )),
+    }
+}
+
+/// Renders the template, creates the state DB and runs post-init steps in `stage_dir`.
+fn build_monorepo(
+    stage_dir: &Path,
+    repo_name: &str,
+    template: &str,
+    provided: HashMap<String, String>,
+    accept_defaults: bool,
+    skip_git: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let (template, manifest) =
+        create_monorepo_from_template(stage_dir, repo_name, template, provided, accept_defaults)?;
+
+    let db_path = stage_dir.join(".apix").join("state.db");
+    Db::create_db_and_migrate(&db_path)
+        .map_err(|e| format!("Failed to create and migrate DB: {}", e))?;
+
+    if let Some(manifest) = manifest {
+        run_post_init(stage_dir, &manifest.post_init, skip_git)
+            .map_err(|e| format!("Post-init step failed: {}", e))?;
+    }
+
+    Ok(template)
+}
+
+/// Renames the staging folder to `dst_path`, or moves its entries into an
+/// existing folder without overwriting anything.
+fn move_into_place(staging: TempDir, dst_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if !dst_path.exists() {
+        let staged = staging.keep();
+        if let Err(e) = fs::rename(&staged, dst_path) {
+            let _ = fs::remove_dir_all(&staged);
+            return Err(e.into());
+        }
+        return Ok(());
+    }
+
+    let entries: Vec<_> = fs::read_dir(staging.path())?
+        .map(|entry| entry.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+
+    let conflicts: Vec<String> = entries
+        .iter()
+        .filter(|name| dst_path.join(name).exists())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(format!("would overwrite {}", conflicts.join(", ")).into());
+    }
+
+    let mut moved = Vec::new();
+    for name in &entries {
+        if let Err(e) = fs::rename(staging.path().join(name), dst_path.join(name)) {
+            for name in moved.iter().rev() {
+                let _ = fs::rename(dst_path.join(name), staging.path().join(name));
+            }
+            return Err(e.into());
+        }
+        moved.push(name);
+    }
+
+    Ok(())
+}
+
 fn create_monorepo_from_template(
     dst_path: &Path,
-    template: &String,
-) -> Result<String, Box<dyn std::error::Error>> {
+    repo_name: &str,
+    template: &str,
+    mut provided: HashMap<String, String>,
+    accept_defaults: bool,
+) -> Result<(String, Option<TemplateManifest>), Box<dyn std::error::Error>> {
     let internal_dir = get_internal_dir();
 
-    let meta_content = internal_dir
-        .read_file_string("templates", "meta.yml")
-        .expect("Failed to read meta.yml");
+    let resolved = resolve_template(
+        internal_dir.get_templates_dir(),
+        TemplateSource::parse(template),
+    )?;
+    let manifest = TemplateManifest::load(&resolved.dir)?;
 
-    let meta: MetaYaml = serde_yaml::from_str(&meta_content).expect("Failed to parse meta.yml");
+    provided.insert("repo_name".to_string(), repo_name.to_string());
+    provided.insert("template".to_string(), resolved.name.clone());
 
-    let template = if template == "default" {
-        meta.default
-    } else {
-        template.to_string()
+    let vars = match &manifest {
+        Some(manifest) => manifest.resolve_variables(&provided, |variable| {
+            let default = variable.default_value();
+
+            if accept_defaults {
+                return default.ok_or_else(|| {
+                    format!(
+                        "No value for '{}', pass it with --set {}=<value>",
+                        variable.name, variable.name
+                    )
+                    .into()
+                });
+            }
+
+            match ask(&variable.prompt_text(), default.as_deref()) {
+                answer if !answer.is_empty() => Ok(answer),
+                _ => Err(format!("A value for '{}' is required", variable.name).into()),
+            }
+        })?,
+        None => provided,
     };
 
-    if !meta.templates.contains(&template) {
-        return Err(format!("Template '{}' not found", template).into());
-    }
+    render_template(&resolved.dir, dst_path, manifest.as_ref(), &vars)?;
 
-    let template_dir = internal_dir.get_templates_dir().join(&template);
+    Ok((resolved.name, manifest))
+}
 
-    if !template_dir.exists() {
-        return Err(format!(
-            "Selected template '{}' folder not found in templates directory",
-            template
-        )
-        .into());
+fn run_post_init(
+    dst_path: &Path,
+    post_init: &PostInit,
+    skip_git: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !post_init.register_plugins.is_empty() {
+        let internal_dir = get_internal_dir();
+        let mut editor = MonorepoConfigEditor::open(dst_path)?;
+
+        for (plugin, version) in &post_init.register_plugins {
+            editor.set_plugin_version(plugin, version)?;
+
+            if !internal_dir.get_plugins_dir().join(plugin).exists() {
+                warn!(
+                    "Plugin '{}' is registered in monorepo.toml but not installed",
+                    plugin
+                );
+            }
+        }
+
+        editor.save()?;
+        info!(
+            "Registered {} plugin(s) in monorepo.toml",
+            post_init.register_plugins.len()
+        );
     }
 
-    if let Err(e) = utils::fs::copy_dir_recursive(&template_dir, &dst_path) {
-        return Err(Box::new(e));
+    if post_init.git_init && skip_git {
+        info!("Skipping git init, the destination is already a git repository");
+    } else if post_init.git_init {
+        init_repo(dst_path)?;
+
+        if let Some(message) = &post_init.initial_commit {
+            commit_all(dst_path, message)?;
+        }
     }
 
-    Ok(template)
+    Ok(())
 }
@@ -1,4 +1,11 @@
+pub mod adopt;
+pub mod doctor;
 pub mod init;
 pub mod install;
 pub mod plugin;
+pub mod plugin_dev;
+pub mod plugins;
+pub mod run;
+pub mod status;
+pub mod template;
 pub mod update;
@@ -0,0 +1,87 @@
+use apix_core::plugin::{
+    args::{add_declared_args, declared_values, event_args, validate_args},
+    hooks::AppliedChanges,
+};
+use clap::{Args, Command, FromArgMatches};
+use log::{error, info};
+
+use crate::{
+    cli::{TargetFlags, cli::CommonFlags},
+    plugin::{
+        apply::{add_registration, record_event, report_registration, review_and_apply, take_plan},
+        helpers::{registered_plugin_config, resolve_plugin, resolve_target},
+        hooks::run_hooks,
+    },
+    utils::git::ensure_clean_tree,
+};
+
+/// Runs a command declared under `[commands.<name>]` in plugin.toml. `argv`
+/// starts with the command name.
+pub fn call_plugin_command(plugin: String, argv: Vec<String>) {
+    let name = argv[0].clone();
+    let plugin_config = registered_plugin_config(&plugin);
+
+    let Some(spec) = plugin_config.commands.get(&name) else {
+        error!("Plugin '{}' has no command '{}'", plugin, name);
+        if !plugin_config.commands.is_empty() {
+            let available: Vec<&str> = plugin_config.commands.keys().map(|k| k.as_str()).collect();
+            info!("Commands of '{}': {}", plugin, available.join(", "));
+        }
+        std::process::exit(1);
+    };
+
+    // Declared arguments are checked before clap, which panics on conflicting flags.
+    if let Err(e) = validate_args(&spec.args) {
+        error!(
+            "Plugin '{}' is invalid: plugin.toml: command '{}': {}",
+            plugin, name, e
+        );
+        std::process::exit(1);
+    }
+
+    let command = Command::new(name.clone())
+        .bin_name(format!("apix plugin {} {}", plugin, name))
+        .about(spec.description.clone());
+    let command = TargetFlags::augment_args(CommonFlags::augment_args(add_declared_args(
+        command, &spec.args,
+    )));
+
+    let matches = command
+        .try_get_matches_from(&argv)
+        .unwrap_or_else(|e| e.exit());
+    let CommonFlags {
+        allow_dirty,
+        accept_all,
+    } = CommonFlags::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let target = TargetFlags::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let values = declared_values(&matches, &spec.args);
+
+    ensure_clean_tree(allow_dirty);
+
+    let (_, abi, ctx) = resolve_plugin(&plugin, resolve_target(target));
+
+    match abi.command(&spec.function_name(&name), &values) {
+        Ok(0) => {}
+        Ok(code) => {
+            error!("Plugin '{}' {} exited with code {}", plugin, name, code);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            error!("Plugin '{}' {} failed: {}", plugin, name, e);
+            std::process::exit(1);
+        }
+    }
+
+    let mut plan = take_plan(&ctx);
+    add_registration(&mut plan, &ctx, &plugin);
+
+    if review_and_apply(&plan, accept_all) {
+        report_registration(&ctx);
+        // Parsed values, so apix's own flags and the spelling of the
+        // arguments do not end up in the history.
+        record_event(&ctx, &name, &event_args(&values));
+
+        let changes = AppliedChanges::from_plan(&plugin, &plan, ctx.borrow().created.clone());
+        run_hooks(&changes, accept_all);
+    }
+}
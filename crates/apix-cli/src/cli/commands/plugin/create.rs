@@ -1,8 +1,18 @@
 use std::{cell::RefCell, rc::Rc};
 
-use apix_core::plugin::{instance::PluginInstance, plugin_ctx::ctx::PluginCtx};
-
-use crate::{cli::cli::CommonFlags, utils::git::ensure_clean_tree};
+use apix_core::plugin::{
+    hooks::AppliedChanges, instance::PluginInstance, plugin_ctx::ctx::PluginCtx,
+};
+use log::error;
+
+use crate::{
+    cli::cli::CommonFlags,
+    plugin::{
+        apply::{add_registration, record_event, report_registration, review_and_apply, take_plan},
+        hooks::run_hooks,
+    },
+    utils::git::ensure_clean_tree,
+};
 
 pub fn call_plugin_create(
     CommonFlags {
@@ -16,22 +26,26 @@ pub fn call_plugin_create(
 ) {
     ensure_clean_tree(allow_dirty);
 
-    let res = abi.create(name).unwrap();
-
-    let proposals = &ctx.borrow().proposals;
-
-    // validate proposals
-
-    // write to tmp folder
-
-    // if error happens, rollback by deleting tmp folder
-
-    // if no errors happen, write  the same
-
-    // create project changes in the same visuals like git with git status
-
-    // if accept_changes is true, execute those proposals
-    // else ask first
-
-    // on decline, end here
+    match abi.create(name.clone()) {
+        Ok(0) => {}
+        Ok(code) => {
+            error!("Plugin '{}' create exited with code {}", plugin, code);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            error!("Plugin '{}' create failed: {}", plugin, e);
+            std::process::exit(1);
+        }
+    }
+
+    let mut plan = take_plan(&ctx);
+    add_registration(&mut plan, &ctx, &plugin);
+
+    if review_and_apply(&plan, accept_all) {
+        report_registration(&ctx);
+        record_event(&ctx, "create", &[name]);
+
+        let changes = AppliedChanges::from_plan(&plugin, &plan, ctx.borrow().created.clone());
+        run_hooks(&changes, accept_all);
+    }
 }
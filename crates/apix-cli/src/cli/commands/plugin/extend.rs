@@ -1,8 +1,19 @@
-use std::{cell::RefCell, rc::Rc};
+use apix_core::{
+    monorepo::config::MonorepoTarget,
+    plugin::{args::event_args, hooks::AppliedChanges},
+};
+use log::{error, info, warn};
 
-use apix_core::plugin::{instance::PluginInstance, plugin_ctx::ctx::PluginCtx};
-
-use crate::{cli::cli::CommonFlags, utils::git::ensure_clean_tree};
+use crate::{
+    cli::{cli::CommonFlags, commands::plugin::info::plugin_info},
+    plugin::{
+        apply::{add_registration, record_event, report_registration, review_and_apply, take_plan},
+        args::{parse_declared, wants_help},
+        helpers::{registered_plugin_config, resolve_plugin},
+        hooks::run_hooks,
+    },
+    utils::git::ensure_clean_tree,
+};
 
 pub fn call_plugin_extend(
     CommonFlags {
@@ -10,19 +21,94 @@ pub fn call_plugin_extend(
         accept_all,
     }: CommonFlags,
     args: Vec<String>,
-    abi: PluginInstance,
-    ctx: Rc<RefCell<PluginCtx>>,
+    force: bool,
+    plugin: String,
+    target: Option<MonorepoTarget>,
 ) {
+    let plugin_config = registered_plugin_config(&plugin);
+
+    if wants_help(&args) {
+        let (_, abi, _) = resolve_plugin(&plugin, None);
+        match plugin_info(&plugin, &plugin_config, &abi) {
+            Some(info) => println!(
+                "\n{}",
+                info.format(&plugin, &plugin_config.version, &plugin_config.description)
+            ),
+            None => info!("Plugin '{}' did not return any info", plugin),
+        }
+        return;
+    }
+
+    // Arguments declared in plugin.toml are checked before any plugin code runs.
+    let mut values = (!plugin_config.extend.args.is_empty())
+        .then(|| parse_declared(&plugin, "extend", &plugin_config.extend.args, &args));
+
     ensure_clean_tree(allow_dirty);
 
-    let res = abi.extend(args).unwrap();
+    let (_, abi, ctx) = resolve_plugin(&plugin, target);
+    if values.is_none() {
+        let declared = abi.extend_args(&plugin_config).unwrap_or_else(|e| {
+            error!("Plugin '{}' failed to provide info: {}", plugin, e);
+            std::process::exit(1);
+        });
+        // `info()` runs on the same ctx, drop anything it proposed.
+        ctx.borrow_mut().proposals.clear();
+        ctx.borrow_mut().created.clear();
+        if !declared.is_empty() {
+            values = Some(parse_declared(&plugin, "extend", &declared, &args));
+        }
+    }
+
+    // Declared arguments are keyed on their parsed values, so `--mode=release`
+    // and `--mode release` are the same extend.
+    let recorded = values.as_ref().map_or_else(|| args.clone(), event_args);
+    let already_applied = match &ctx.borrow().history {
+        Some(history) => history.contains("extend", &recorded).unwrap_or_else(|e| {
+            error!("Failed to query event history: {}", e);
+            std::process::exit(1);
+        }),
+        None => false,
+    };
+
+    if already_applied {
+        if !force {
+            warn!(
+                "extend {:?} was already applied to this target, skipping. Use --force to apply it again.",
+                args
+            );
+            return;
+        }
+
+        warn!(
+            "extend {:?} was already applied, re-applying due to --force",
+            args
+        );
+    }
 
-    let proposals = &ctx.borrow().proposals;
+    let result = match &values {
+        Some(values) => abi.extend_with(values),
+        None => abi.extend(args.clone()),
+    };
+    match result {
+        Ok(0) => {}
+        Ok(code) => {
+            error!("Plugin extend exited with code {}", code);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            error!("Plugin extend failed: {}", e);
+            std::process::exit(1);
+        }
+    }
 
-    // create project changes in the same visuals like git with git status
+    let mut plan = take_plan(&ctx);
+    add_registration(&mut plan, &ctx, &plugin);
 
-    // if accept_changes is true, execute those proposals
-    // else ask first
+    if review_and_apply(&plan, accept_all) {
+        report_registration(&ctx);
+        record_event(&ctx, "extend", &recorded);
 
-    // on decline, end here
+        let changes = AppliedChanges::from_plan(&plugin, &plan, ctx.borrow().created.clone());
+        run_hooks(&changes, accept_all);
+    }
 }
@@ -1,18 +1,60 @@
-use apix_core::plugin::{config::PluginConfig, instance::PluginInstance};
+use apix_core::plugin::{
+    config::PluginConfig, instance::PluginInstance, plugin_ctx::info::PluginInfo,
+};
 use log::{error, info};
 
 pub fn call_plugin_info(plugin: String, plugin_config: PluginConfig, abi: PluginInstance) {
-    match abi.info() {
-        Ok(Some(info)) => {
+    match plugin_info(&plugin, &plugin_config, &abi) {
+        Some(info) => {
             println!(
                 "\n{}",
                 info.format(&plugin, &plugin_config.version, &plugin_config.description)
             );
         }
-        Ok(None) => info!("Plugin '{}' did not return any info", plugin),
-        Err(e) => {
+        None => info!("Plugin '{}' did not return any info", plugin),
+    }
+
+    if !plugin_config.commands.is_empty() {
+        println!("Commands:");
+        for (name, command) in &plugin_config.commands {
+            println!("  {:<12} {}", name, command.description);
+        }
+        println!(
+            "\nRun 'apix plugin {} <command> --help' for its arguments.",
+            plugin
+        );
+    }
+}
+
+/// What `info()` returns, with the `extend` arguments of plugin.toml taking
+/// precedence over the ones it declares.
+pub fn plugin_info(
+    plugin: &str,
+    plugin_config: &PluginConfig,
+    abi: &PluginInstance,
+) -> Option<PluginInfo> {
+    let declared = !plugin_config.extend.args.is_empty();
+    let info = if plugin_config.supported.actions.iter().any(|a| a == "info") {
+        abi.info().unwrap_or_else(|e| {
             error!("Plugin '{}' failed to provide info: {}", plugin, e);
             std::process::exit(1);
+        })
+    } else {
+        None
+    };
+
+    match info {
+        Some(mut info) => {
+            if declared {
+                info.args = plugin_config.extend.args.clone();
+            }
+            Some(info)
         }
+        None if declared => Some(PluginInfo {
+            usage: Vec::new(),
+            options: Vec::new(),
+            args: plugin_config.extend.args.clone(),
+        }),
+        None => None,
     }
 }
@@ -1,38 +1,153 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, slice};
 
-use apix_core::plugin::{
-    config::PluginConfig, instance::PluginInstance, plugin_ctx::ctx::PluginCtx,
+use apix_core::{
+    monorepo::{config::MonorepoTarget, editor::MonorepoConfigEditor},
+    plugin::{
+        config::PluginConfig, file_tree::PendingFiles, hooks::AppliedChanges,
+        instance::PluginInstance, plugin_ctx::ctx::PluginCtx,
+    },
 };
+use log::{error, info, warn};
 
-use crate::{cli::cli::CommonFlags, utils::git::ensure_clean_tree};
+use crate::{
+    cli::cli::CommonFlags,
+    plugin::{
+        apply::{
+            record_event, report_registration, try_add_registration, try_review_and_apply,
+            try_take_plan,
+        },
+        helpers::{migration_plugin_config, try_load_resolved_plugin},
+        hooks::run_hooks,
+    },
+    utils::git::ensure_clean_tree,
+};
 
+/// Runs migrate of the newer plugin version once per target. `None` migrates
+/// the repo as a whole. Targets that already ran this migration are skipped,
+/// and a failing target does not stop the others.
 pub fn call_plugin_migrate(
     CommonFlags {
         allow_dirty,
         accept_all,
     }: CommonFlags,
-    plugin_config: PluginConfig,
-    abi: PluginInstance,
-    ctx: Rc<RefCell<PluginCtx>>,
+    plugin: String,
+    to: Option<String>,
+    targets: Vec<Option<MonorepoTarget>>,
 ) {
+    let Some((plugin_config, from_version)) = migration_plugin_config(&plugin, to.as_deref())
+    else {
+        return;
+    };
+
     ensure_clean_tree(allow_dirty);
 
-    // get version of plugin from plugin.toml
-    // and used version from monorepo.toml
-    // check the difference
-    // if they are different and the monorepo.toml version is smaller than the plugin
-    // run it
+    info!(
+        "Migrating '{}' from v{} to v{}",
+        plugin, from_version, plugin_config.version
+    );
+
+    let mut all_applied = true;
+    let mut changes = AppliedChanges {
+        plugin: plugin.clone(),
+        ..Default::default()
+    };
+
+    for target in targets {
+        if let Some(target) = &target {
+            info!("Migrating {} '{}'", target.kind.as_str(), target.name);
+        }
+
+        let result = try_load_resolved_plugin(&plugin, &plugin_config, target, PendingFiles::new())
+            .and_then(|(abi, ctx)| {
+                migrate_target(
+                    &plugin_config,
+                    &from_version,
+                    abi,
+                    &ctx,
+                    accept_all,
+                    &mut changes,
+                )
+            });
+
+        match result {
+            Ok(applied) => all_applied &= applied,
+            Err(e) => {
+                error!("{}", e);
+                all_applied = false;
+            }
+        }
+    }
+
+    if !all_applied {
+        warn!(
+            "Not all migrations were applied, keeping v{} of '{}' in monorepo.toml",
+            from_version, plugin
+        );
+        run_hooks(&changes, accept_all);
+        return;
+    }
+
+    let version = plugin_config.version;
+    let monorepo_root = std::env::current_dir().unwrap();
+    let result = MonorepoConfigEditor::open(&monorepo_root).and_then(|mut editor| {
+        editor.set_plugin_version(&plugin, &version)?;
+        editor.save()
+    });
+
+    match result {
+        Ok(()) => info!("Updated '{}' to v{} in monorepo.toml", plugin, version),
+        Err(e) => {
+            error!("Failed to update monorepo.toml: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    changes.updated = Some((plugin, version));
+    run_hooks(&changes, accept_all);
+}
+
+fn migrate_target(
+    plugin_config: &PluginConfig,
+    from_version: &str,
+    abi: PluginInstance,
+    ctx: &Rc<RefCell<PluginCtx>>,
+    accept_all: bool,
+    changes: &mut AppliedChanges,
+) -> Result<bool, String> {
+    let migrated = slice::from_ref(&plugin_config.version);
+    let already_applied = match &ctx.borrow().history {
+        Some(history) => history
+            .contains("migrate", migrated)
+            .map_err(|e| format!("Failed to query event history: {}", e))?,
+        None => false,
+    };
+    if already_applied {
+        info!("Already migrated to v{}, skipping", plugin_config.version);
+        return Ok(true);
+    }
 
-    let res = abi.migrate(plugin_config.version).unwrap();
+    match abi.migrate(from_version.to_string()) {
+        Ok(0) => {}
+        Ok(code) => return Err(format!("Plugin migrate exited with code {}", code)),
+        Err(e) => return Err(format!("Plugin migrate failed: {}", e)),
+    }
 
-    let proposals = &ctx.borrow().proposals;
+    let mut plan = try_take_plan(ctx)?;
+    try_add_registration(&mut plan, ctx, &plugin_config.name)?;
 
-    // create project changes in the same visuals like git with git status
+    if !plan.is_empty() {
+        if !try_review_and_apply(&plan, accept_all)? {
+            return Ok(false);
+        }
+        report_registration(ctx);
+    }
 
-    // if accept_changes is true, execute those proposals
-    // else ask first
+    record_event(ctx, "migrate", migrated);
 
-    // on decline, end here
+    let applied =
+        AppliedChanges::from_plan(&plugin_config.name, &plan, ctx.borrow().created.clone());
+    changes.created.extend(applied.created);
+    changes.files.extend(applied.files);
 
-    // update version in monorepo.toml
+    Ok(true)
 }
@@ -1,3 +1,4 @@
+pub mod command;
 pub mod create;
 pub mod extend;
 pub mod info;
@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use apix_core::plugin::{
+    check::{Analyzer, check_plugin},
+    validate::{Severity, has_errors},
+};
+use log::{error, info, warn};
+
+pub fn check_plugin_dir(path: PathBuf) {
+    let report = check_plugin(&path).unwrap_or_else(|e| {
+        error!("Failed to check plugin: {}", e);
+        std::process::exit(1);
+    });
+
+    match report.analyzer {
+        Analyzer::LuauLsp => info!(
+            "Checked '{}' ({})",
+            report.entry.display(),
+            report.analyzer.scope()
+        ),
+        Analyzer::Builtin => warn!(
+            "Checked '{}' ({})",
+            report.entry.display(),
+            report.analyzer.scope()
+        ),
+    }
+
+    for diagnostic in &report.diagnostics {
+        println!("{}", diagnostic);
+    }
+
+    let errors = report
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = report.diagnostics.len() - errors;
+
+    if has_errors(&report.diagnostics) {
+        error!("{} error(s), {} warning(s)", errors, warnings);
+        std::process::exit(1);
+    }
+    if warnings > 0 {
+        warn!("{} warning(s)", warnings);
+    } else {
+        info!("No problems found");
+    }
+}
@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+use apix_core::plugin::scaffold::link_plugin;
+use log::{error, info};
+
+use crate::utils::internal_dir::get_internal_dir;
+
+pub fn link_plugin_dir(path: PathBuf) {
+    let internal_dir = get_internal_dir();
+
+    match link_plugin(&path, internal_dir.get_plugins_dir()) {
+        Ok(link) => info!("Linked '{}' to '{}'", link.display(), path.display()),
+        Err(e) => {
+            error!("Failed to link plugin: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod check;
+pub mod link;
+pub mod new;
+pub mod test;
+pub mod types;
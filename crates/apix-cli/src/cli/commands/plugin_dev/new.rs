@@ -0,0 +1,19 @@
+use apix_core::plugin::scaffold::{PluginLang, scaffold_plugin};
+use log::{error, info};
+
+pub fn new_plugin(name: String, lang: PluginLang) {
+    let dst_dir = std::env::current_dir().unwrap().join(&name);
+
+    if let Err(e) = scaffold_plugin(&dst_dir, &name, lang) {
+        error!("Failed to create plugin '{}': {}", name, e);
+        std::process::exit(1);
+    }
+
+    info!(
+        "Created {} plugin '{}' in '{}', run 'apix plugin-dev link {}' to try it out",
+        lang.extension(),
+        name,
+        dst_dir.display(),
+        name
+    );
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use ansi_term::Colour::{Green, Red, Yellow};
+use apix_core::plugin::testing::{SnapshotResult, check_snapshot, discover_cases, run_plugin_test};
+use log::{error, info};
+
+use crate::utils::internal_dir::get_internal_dir;
+
+pub fn test_plugin(path: PathBuf, case_filter: Option<String>, update: bool) {
+    let cases = discover_cases(&path).unwrap_or_else(|e| {
+        error!("Failed to read test cases: {}", e);
+        std::process::exit(1);
+    });
+
+    let cases: Vec<_> = cases
+        .into_iter()
+        .filter(|c| case_filter.as_ref().is_none_or(|f| &c.name == f))
+        .collect();
+
+    if cases.is_empty() {
+        info!("No test cases found in '{}'", path.join("tests").display());
+        return;
+    }
+
+    let internal_dir = get_internal_dir();
+
+    let mut failed = 0;
+    for case in &cases {
+        let result = run_plugin_test(&path, internal_dir.get_plugins_dir(), case)
+            .and_then(|outcome| check_snapshot(&path, case, &outcome, update));
+
+        match result {
+            Ok(SnapshotResult::Matched) => println!("{} {}", Green.paint("pass   "), case.name),
+            Ok(SnapshotResult::Updated) => println!("{} {}", Yellow.paint("updated"), case.name),
+            Ok(SnapshotResult::Missing) => {
+                failed += 1;
+                println!(
+                    "{} {}: no snapshot yet, run with --update to record it",
+                    Red.paint("fail   "),
+                    case.name
+                );
+            }
+            Ok(SnapshotResult::Mismatch { expected, actual }) => {
+                failed += 1;
+                println!("{} {}: snapshot differs", Red.paint("fail   "), case.name);
+                print_diff(&expected, &actual);
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{} {}: {}", Red.paint("error  "), case.name, e);
+            }
+        }
+    }
+
+    println!("\n{} passed, {} failed", cases.len() - failed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Line diff based on the longest common subsequence, good enough for snapshots.
+fn print_diff(expected: &str, actual: &str) {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            println!("    {}", Green.paint(format!("+ {}", new[j])));
+            j += 1;
+        } else {
+            println!("    {}", Red.paint(format!("- {}", old[i])));
+            i += 1;
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::{fs, path::PathBuf};
+
+use apix_core::plugin::types::{DEFINITIONS_FILE, generate_definitions};
+use log::{error, info};
+
+/// Prints the ctx definitions, or writes them to `output` (a file, or a folder
+/// that receives `apix.d.luau`).
+pub fn write_types(output: Option<PathBuf>) {
+    let definitions = match generate_definitions() {
+        Ok(definitions) => definitions,
+        Err(e) => {
+            error!("Failed to generate type definitions: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let Some(output) = output else {
+        print!("{}", definitions);
+        return;
+    };
+
+    let path = if output.is_dir() {
+        output.join(DEFINITIONS_FILE)
+    } else {
+        output
+    };
+
+    match fs::write(&path, definitions) {
+        Ok(_) => info!("Wrote type definitions to '{}'", path.display()),
+        Err(e) => {
+            error!("Failed to write '{}': {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
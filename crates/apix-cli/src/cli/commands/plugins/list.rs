@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use ansi_term::Colour::Green;
+use apix_core::{
+    monorepo::config::get_monorepo_config,
+    plugin::config::{InstalledPlugin, list_installed_plugins, resolve_version},
+};
+use log::error;
+use serde_json::json;
+
+use crate::utils::internal_dir::get_internal_dir;
+
+pub fn list_plugins(as_json: bool) {
+    let plugins = installed_plugins();
+    print_plugins(&plugins, as_json);
+}
+
+pub fn installed_plugins() -> Vec<InstalledPlugin> {
+    let internal_dir = get_internal_dir();
+
+    list_installed_plugins(internal_dir.get_plugins_dir()).unwrap_or_else(|e| {
+        error!("Failed to read installed plugins: {}", e);
+        std::process::exit(1);
+    })
+}
+
+pub fn print_plugins(plugins: &[InstalledPlugin], as_json: bool) {
+    let required = required_versions(plugins);
+
+    if as_json {
+        let entries: Vec<_> = plugins
+            .iter()
+            .map(|plugin| {
+                let latest = plugin.latest();
+                json!({
+                    "name": plugin.name,
+                    "description": latest.map(|c| c.description.as_str()),
+                    "versions": plugin.versions.iter().map(|(v, _)| v.to_string()).collect::<Vec<_>>(),
+                    "required": required.get(&plugin.name),
+                    "supported": latest.map(|c| &c.supported),
+                })
+            })
+            .collect();
+
+        println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        return;
+    }
+
+    if plugins.is_empty() {
+        println!("No plugins found");
+        return;
+    }
+
+    for plugin in plugins {
+        let Some(latest) = plugin.latest() else {
+            continue;
+        };
+
+        let versions: Vec<String> = plugin
+            .versions
+            .iter()
+            .map(|(version, _)| {
+                let version = version.to_string();
+                if required.get(&plugin.name) == Some(&version) {
+                    Green.paint(format!("{} (required)", version)).to_string()
+                } else {
+                    version
+                }
+            })
+            .collect();
+
+        println!("{} - {}", plugin.name, latest.description);
+        println!("  versions:  {}", versions.join(", "));
+        println!("  actions:   {}", or_dash(&latest.supported.actions));
+        println!("  languages: {}", or_dash(&latest.supported.languages));
+        println!("  features:  {}", or_dash(&latest.supported.features));
+    }
+}
+
+/// Installed version each plugin resolves to for the `monorepo.toml` in the
+/// current folder, if there is one.
+fn required_versions(plugins: &[InstalledPlugin]) -> HashMap<String, String> {
+    let monorepo_root = std::env::current_dir().unwrap();
+    if !monorepo_root.join("monorepo.toml").exists() {
+        return HashMap::new();
+    }
+
+    let config = match get_monorepo_config(&monorepo_root) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Error reading monorepo config: {}", e);
+            return HashMap::new();
+        }
+    };
+
+    plugins
+        .iter()
+        .filter_map(|plugin| {
+            let requested = config.plugins.get(&plugin.name)?.version();
+            let versions: Vec<_> = plugin.versions.iter().map(|(v, _)| v.clone()).collect();
+            let resolved = resolve_version(&versions, requested)?;
+            Some((plugin.name.clone(), resolved.to_string()))
+        })
+        .collect()
+}
+
+fn or_dash(values: &[String]) -> String {
+    if values.is_empty() {
+        "-".to_string()
+    } else {
+        values.join(", ")
+    }
+}
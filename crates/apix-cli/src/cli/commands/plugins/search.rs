@@ -0,0 +1,43 @@
+use crate::cli::commands::plugins::list::{installed_plugins, print_plugins};
+
+/// Matches `term` against the name, description, languages and features of the
+/// newest installed version. `--language`/`--feature` narrow the search further.
+pub fn search_plugins(
+    term: Option<String>,
+    language: Option<String>,
+    feature: Option<String>,
+    as_json: bool,
+) {
+    let term = term.map(|t| t.to_lowercase());
+
+    let plugins: Vec<_> = installed_plugins()
+        .into_iter()
+        .filter(|plugin| {
+            let Some(config) = plugin.latest() else {
+                return false;
+            };
+            let supported = &config.supported;
+
+            let matches_term = term.as_ref().is_none_or(|term| {
+                plugin.name.to_lowercase().contains(term)
+                    || config.description.to_lowercase().contains(term)
+                    || contains_ignore_case(&supported.languages, term)
+                    || contains_ignore_case(&supported.features, term)
+            });
+            let matches_language = language
+                .as_ref()
+                .is_none_or(|l| contains_ignore_case(&supported.languages, l));
+            let matches_feature = feature
+                .as_ref()
+                .is_none_or(|f| contains_ignore_case(&supported.features, f));
+
+            matches_term && matches_language && matches_feature
+        })
+        .collect();
+
+    print_plugins(&plugins, as_json);
+}
+
+fn contains_ignore_case(values: &[String], value: &str) -> bool {
+    values.iter().any(|v| v.eq_ignore_ascii_case(value))
+}
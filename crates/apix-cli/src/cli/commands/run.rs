@@ -0,0 +1,229 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use apix_core::{
+    monorepo::{
+        config::get_monorepo_config,
+        editor::MonorepoConfigEditor,
+        recipe::{RecipeStep, find_recipe},
+    },
+    plugin::{
+        args::{ArgValues, event_args},
+        file_tree::PendingFiles,
+        hooks::AppliedChanges,
+        plan::Plan,
+        plugin_ctx::ctx::{PluginCtx, Proposal},
+    },
+};
+use log::{error, info, warn};
+
+use crate::{
+    cli::cli::CommonFlags,
+    plugin::{
+        apply::{
+            MONOREPO_FILE, record_event, report_registration, review_and_apply, stage_registration,
+            take_plan,
+        },
+        args::parse_declared,
+        helpers::resolve_plugin_with_pending,
+        hooks::run_hooks,
+    },
+    utils::git::ensure_clean_tree,
+};
+
+/// Runs the steps of a recipe one after the other, each seeing the changes
+/// proposed before it, and applies everything as a single plan. As in any
+/// plan, the system commands of all steps run in step order after every file
+/// change was written, not between the steps.
+pub fn run_recipe(
+    recipe: String,
+    set: Vec<(String, String)>,
+    CommonFlags {
+        allow_dirty,
+        accept_all,
+    }: CommonFlags,
+) {
+    ensure_clean_tree(allow_dirty);
+
+    let monorepo_root = std::env::current_dir().unwrap();
+    let mut config = get_monorepo_config(&monorepo_root).unwrap_or_else(|e| {
+        error!("Error reading monorepo config: {}", e);
+        std::process::exit(1);
+    });
+
+    let found = find_recipe(&monorepo_root, &config, &recipe).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    });
+    let vars: HashMap<String, String> = set.into_iter().collect();
+    let steps = found.render(&vars).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    });
+    if let Some(description) = &found.description {
+        info!("{}", description);
+    }
+
+    let mut editor = MonorepoConfigEditor::open(&monorepo_root).unwrap_or_else(|e| {
+        error!("Failed to open monorepo.toml: {}", e);
+        std::process::exit(1);
+    });
+    let mut registered = false;
+    let mut pending = PendingFiles::new();
+    let mut proposals = Vec::new();
+    let mut events: Vec<(Rc<RefCell<PluginCtx>>, String, Vec<String>)> = Vec::new();
+    let mut applied = Vec::new();
+
+    for (i, step) in steps.iter().enumerate() {
+        let label = format!(
+            "Step {}/{} ({} {})",
+            i + 1,
+            steps.len(),
+            step.plugin,
+            step.action
+        );
+        info!("{}: {}", label, step.args.join(" "));
+
+        // Targets may be projects or packages created by earlier steps.
+        let target = match (&step.project, &step.package) {
+            (Some(name), _) => Some(config.project_target(name).unwrap_or_else(|| {
+                error!("{}: project '{}' is not registered", label, name);
+                std::process::exit(1);
+            })),
+            (_, Some(name)) => Some(config.package_target(name).unwrap_or_else(|| {
+                error!("{}: package '{}' is not registered", label, name);
+                std::process::exit(1);
+            })),
+            (None, None) => None,
+        };
+
+        let (plugin_config, abi, ctx) =
+            resolve_plugin_with_pending(&step.plugin, target, pending.clone());
+        let values = match step.action.as_str() {
+            "create" => None,
+            _ => {
+                let declared = abi.extend_args(&plugin_config).unwrap_or_else(|e| {
+                    error!("{}: failed to read the extend arguments: {}", label, e);
+                    std::process::exit(1);
+                });
+                // `info()` runs on the same ctx, drop anything it proposed.
+                ctx.borrow_mut().proposals.clear();
+                ctx.borrow_mut().created.clear();
+                (!declared.is_empty())
+                    .then(|| parse_declared(&step.plugin, "extend", &declared, &step.args))
+            }
+        };
+        let Some(args) = step_args(step, values.as_ref(), &ctx, &label) else {
+            continue;
+        };
+
+        let result = match (step.action.as_str(), &values) {
+            ("create", _) => abi.create(args[0].clone()),
+            (_, Some(values)) => abi.extend_with(values),
+            (_, None) => abi.extend(args.clone()),
+        };
+        match result {
+            Ok(0) => {}
+            Ok(code) => {
+                error!("{} exited with code {}", label, code);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                error!("{} failed: {}", label, e);
+                std::process::exit(1);
+            }
+        }
+
+        let plan = take_plan(&ctx);
+        let changes = plan.file_changes().unwrap_or_else(|e| {
+            error!("{} proposed an invalid change: {}", label, e);
+            std::process::exit(1);
+        });
+        // Registration builds on monorepo.toml as the steps left it.
+        if let Some(Some(content)) = changes.get(Path::new(MONOREPO_FILE)) {
+            editor = MonorepoConfigEditor::parse(monorepo_root.join(MONOREPO_FILE), content)
+                .unwrap_or_else(|e| {
+                    error!("{} proposed an invalid monorepo.toml: {}", label, e);
+                    std::process::exit(1);
+                });
+            config = editor.validate().unwrap_or_else(|e| {
+                error!("{} proposed an invalid monorepo.toml: {}", label, e);
+                std::process::exit(1);
+            });
+        }
+        pending.extend(changes);
+        if !ctx.borrow().created.is_empty() {
+            config = stage_registration(&mut editor, &ctx, &step.plugin);
+            pending.insert(PathBuf::from(MONOREPO_FILE), Some(editor.contents()));
+            registered = true;
+        }
+
+        applied.push(AppliedChanges::from_plan(
+            &step.plugin,
+            &plan,
+            ctx.borrow().created.clone(),
+        ));
+        proposals.extend(plan.proposals);
+        events.push((ctx, step.action.clone(), args));
+    }
+
+    // Registering created units is part of the plan, so it is reviewed and
+    // rolled back with the files of the steps.
+    if registered {
+        proposals.push(Proposal::ModifyFile {
+            path: MONOREPO_FILE.to_string(),
+            content: editor.contents(),
+        });
+    }
+
+    if !review_and_apply(&Plan::new(proposals), accept_all) {
+        return;
+    }
+
+    for (ctx, action, args) in &events {
+        report_registration(ctx);
+        record_event(ctx, action, args);
+    }
+    for changes in &applied {
+        run_hooks(changes, accept_all);
+    }
+}
+
+/// Arguments the step is recorded with, or `None` for an extend that was
+/// already applied to its target. Declared extend arguments are keyed on their
+/// parsed `values`, so equivalent spellings match.
+fn step_args(
+    step: &RecipeStep,
+    values: Option<&ArgValues>,
+    ctx: &Rc<RefCell<PluginCtx>>,
+    label: &str,
+) -> Option<Vec<String>> {
+    if step.action == "create" {
+        return match step.args.as_slice() {
+            [name] => Some(vec![name.clone()]),
+            _ => {
+                error!("{}: create takes exactly one argument, the name", label);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    let args = values.map_or_else(|| step.args.clone(), event_args);
+    let already_applied = match &ctx.borrow().history {
+        Some(history) => history.contains("extend", &args).unwrap_or_else(|e| {
+            error!("Failed to query event history: {}", e);
+            std::process::exit(1);
+        }),
+        None => false,
+    };
+    if already_applied {
+        warn!("{}: {:?} was already applied, skipping", label, step.args);
+        return None;
+    }
+
+    Some(args)
+}
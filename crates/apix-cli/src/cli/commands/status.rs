@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use ansi_term::Colour::{Red, Yellow};
+use apix_core::{
+    db::EventRecord,
+    monorepo::config::{MonorepoConfig, UnitKind, get_monorepo_config},
+    plugin::config::{list_installed_plugins, resolve_version},
+};
+use log::error;
+use semver::Version;
+use serde_json::{Value, json};
+
+use crate::{db::get_db, utils::internal_dir::get_internal_dir};
+
+struct UnitStatus<'a> {
+    name: &'a str,
+    kind: UnitKind,
+    path: &'a str,
+    language: &'a str,
+    plugin: Option<&'a str>,
+    last_event: Option<&'a EventRecord>,
+}
+
+struct PluginStatus {
+    name: String,
+    required: String,
+    installed: Vec<Version>,
+    /// Version the last recorded event was applied with.
+    locked: Option<String>,
+    /// Newest installed version, when it is newer than the required one.
+    pending_migration: Option<Version>,
+    /// The required version is not installed.
+    missing: bool,
+}
+
+pub fn show_status(as_json: bool) {
+    let monorepo_root = std::env::current_dir().unwrap();
+    let config = get_monorepo_config(&monorepo_root).unwrap_or_else(|e| {
+        error!("Error reading monorepo config: {}", e);
+        std::process::exit(1);
+    });
+
+    let db = get_db();
+    let (locked, events) = match db
+        .plugin_versions()
+        .and_then(|v| Ok((v, db.last_events()?)))
+    {
+        Ok(state) => state,
+        Err(e) => {
+            error!("Failed to read state DB: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let units = unit_statuses(&config, &events);
+    let plugins = plugin_statuses(&config, &locked);
+
+    if as_json {
+        print_json(&config, &units, &plugins);
+    } else {
+        print_human(&config, &units, &plugins);
+    }
+}
+
+fn unit_statuses<'a>(config: &'a MonorepoConfig, events: &'a [EventRecord]) -> Vec<UnitStatus<'a>> {
+    let mut units: Vec<UnitStatus> = config
+        .projects
+        .iter()
+        .map(|(name, p)| UnitStatus {
+            name,
+            kind: UnitKind::Project,
+            path: &p.path,
+            language: &p.language,
+            plugin: p.plugin.as_deref(),
+            last_event: events
+                .iter()
+                .find(|e| e.project.as_deref() == Some(name.as_str())),
+        })
+        .chain(config.packages.iter().map(|(name, p)| {
+            UnitStatus {
+                name,
+                kind: UnitKind::Package,
+                path: &p.path,
+                language: &p.language,
+                plugin: p.plugin.as_deref(),
+                last_event: events
+                    .iter()
+                    .find(|e| e.package.as_deref() == Some(name.as_str())),
+            }
+        }))
+        .collect();
+    units.sort_by(|a, b| (a.kind.as_str(), a.name).cmp(&(b.kind.as_str(), b.name)));
+
+    units
+}
+
+fn plugin_statuses(config: &MonorepoConfig, locked: &HashMap<String, String>) -> Vec<PluginStatus> {
+    let internal_dir = get_internal_dir();
+    let installed: HashMap<String, Vec<Version>> =
+        list_installed_plugins(internal_dir.get_plugins_dir())
+            .unwrap_or_else(|e| {
+                error!("Failed to read installed plugins: {}", e);
+                std::process::exit(1);
+            })
+            .into_iter()
+            .map(|p| (p.name, p.versions.into_iter().map(|(v, _)| v).collect()))
+            .collect();
+
+    let mut plugins: Vec<PluginStatus> = config
+        .plugins
+        .iter()
+        .map(|(name, meta)| {
+            let installed = installed.get(name).cloned().unwrap_or_default();
+            let locked = locked.get(name).cloned();
+
+            // `apix plugin <name> migrate` moves from the version pinned in
+            // monorepo.toml to the newest installed one.
+            let required = Version::parse(meta.version()).ok();
+            let pending_migration = match (&required, installed.last()) {
+                (Some(required), Some(newest)) if newest > required => Some(newest.clone()),
+                _ => None,
+            };
+            let missing = match &required {
+                Some(required) => !installed.contains(required),
+                None => installed.is_empty(),
+            };
+
+            PluginStatus {
+                name: name.clone(),
+                required: meta.version().to_string(),
+                installed,
+                locked,
+                pending_migration,
+                missing,
+            }
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+
+    plugins
+}
+
+fn print_human(config: &MonorepoConfig, units: &[UnitStatus], plugins: &[PluginStatus]) {
+    println!(
+        "Repository: {} (version {}, template '{}')",
+        config.repo.name, config.repo.version, config.repo.template
+    );
+
+    for (kind, title) in [
+        (UnitKind::Project, "Projects"),
+        (UnitKind::Package, "Packages"),
+    ] {
+        println!("\n{}:", title);
+
+        let units: Vec<_> = units.iter().filter(|u| u.kind == kind).collect();
+        if units.is_empty() {
+            println!("  (none)");
+        }
+
+        for unit in units {
+            let last_event = match unit.last_event {
+                Some(e) => format!(
+                    "{} {} via {} at {}",
+                    e.action, e.args, e.plugin, e.timestamp
+                ),
+                None => "never".to_string(),
+            };
+            println!(
+                "  {:<20} {:<30} {:<12} plugin: {:<12} last: {}",
+                unit.name,
+                unit.path,
+                unit.language,
+                unit.plugin.unwrap_or("-"),
+                last_event
+            );
+        }
+    }
+
+    println!("\nPlugins:");
+    if plugins.is_empty() {
+        println!("  (none)");
+    }
+
+    for plugin in plugins {
+        let installed = if plugin.installed.is_empty() {
+            Red.paint("missing").to_string()
+        } else {
+            join_versions(&plugin.installed)
+        };
+        println!(
+            "  {:<20} required: {:<10} installed: {:<20} locked: {}",
+            plugin.name,
+            plugin.required,
+            installed,
+            plugin.locked.as_deref().unwrap_or("-")
+        );
+    }
+
+    let pending: Vec<_> = plugins
+        .iter()
+        .filter(|p| p.pending_migration.is_some())
+        .collect();
+    if !pending.is_empty() {
+        println!("\nPending migrations:");
+        for plugin in pending {
+            // `--all` only covers the units a plugin owns, otherwise the repo
+            // as a whole is migrated.
+            let command = if config.targets_owned_by(&plugin.name).is_empty() {
+                "migrate"
+            } else {
+                "migrate --all"
+            };
+            println!(
+                "  {}",
+                Yellow.paint(format!(
+                    "{} {} -> {}, run 'apix plugin {} {}'",
+                    plugin.name,
+                    plugin.required,
+                    plugin.pending_migration.as_ref().unwrap(),
+                    plugin.name,
+                    command
+                ))
+            );
+        }
+    }
+
+    let missing: Vec<_> = plugins.iter().filter(|p| p.missing).collect();
+    if !missing.is_empty() {
+        println!("\nMissing plugins:");
+        for plugin in missing {
+            println!(
+                "  {}",
+                Red.paint(format!(
+                    "{} {}, run 'apix install {} {}'",
+                    plugin.name, plugin.required, plugin.name, plugin.required
+                ))
+            );
+        }
+    }
+}
+
+fn print_json(config: &MonorepoConfig, units: &[UnitStatus], plugins: &[PluginStatus]) {
+    let unit_json = |kind: UnitKind| -> Vec<Value> {
+        units
+            .iter()
+            .filter(|u| u.kind == kind)
+            .map(|u| {
+                json!({
+                    "name": u.name,
+                    "path": u.path,
+                    "language": u.language,
+                    "plugin": u.plugin,
+                    "last_event": u.last_event.map(|e| json!({
+                        "plugin": e.plugin,
+                        "action": e.action,
+                        "args": serde_json::from_str::<Value>(&e.args).unwrap_or(Value::Null),
+                        "timestamp": e.timestamp,
+                    })),
+                })
+            })
+            .collect()
+    };
+
+    let plugin_json: Vec<Value> = plugins
+        .iter()
+        .map(|p| {
+            json!({
+                "name": p.name,
+                "required": p.required,
+                "resolved": resolve_version(&p.installed, &p.required).map(|v| v.to_string()),
+                "installed": p.installed.iter().map(Version::to_string).collect::<Vec<_>>(),
+                "locked": p.locked,
+                "pending_migration": p.pending_migration.as_ref().map(Version::to_string),
+                "missing": p.missing,
+            })
+        })
+        .collect();
+
+    let status = json!({
+        "repo": {
+            "name": config.repo.name,
+            "version": config.repo.version,
+            "template": config.repo.template,
+        },
+        "projects": unit_json(UnitKind::Project),
+        "packages": unit_json(UnitKind::Package),
+        "plugins": plugin_json,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&status).unwrap());
+}
+
+fn join_versions(versions: &[Version]) -> String {
+    versions
+        .iter()
+        .map(Version::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use apix_core::template::source;
+use log::{error, info};
+
+use crate::utils::internal_dir::get_internal_dir;
+
+pub fn add_template(source: String, name: Option<String>, make_default: bool) {
+    let internal_dir = get_internal_dir();
+
+    match source::add_template(
+        internal_dir.get_templates_dir(),
+        Path::new(&source),
+        name.as_deref(),
+        make_default,
+    ) {
+        Ok(name) => info!(
+            "Added template '{}'{}",
+            name,
+            if make_default { " as default" } else { "" }
+        ),
+        Err(e) => {
+            error!("Failed to add template '{}': {}", source, e);
+            std::process::exit(1);
+        }
+    }
+}
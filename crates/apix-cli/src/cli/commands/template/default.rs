@@ -0,0 +1,22 @@
+use apix_core::template::meta::TemplateMeta;
+use log::{error, info};
+
+use crate::utils::internal_dir::get_internal_dir;
+
+pub fn set_default_template(name: String) {
+    let internal_dir = get_internal_dir();
+    let templates_dir = internal_dir.get_templates_dir();
+
+    let result = TemplateMeta::load(templates_dir).and_then(|mut meta| {
+        meta.default = meta.resolve(&name)?;
+        meta.save(templates_dir)
+    });
+
+    match result {
+        Ok(()) => info!("Template '{}' is now the default", name),
+        Err(e) => {
+            error!("Failed to set default template: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use apix_core::template::meta::TemplateMeta;
+use log::error;
+
+use crate::utils::internal_dir::get_internal_dir;
+
+pub fn list_templates() {
+    let internal_dir = get_internal_dir();
+
+    let meta = TemplateMeta::load(internal_dir.get_templates_dir()).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    });
+
+    for template in &meta.templates {
+        if *template == meta.default {
+            println!("{} (default)", template);
+        } else {
+            println!("{}", template);
+        }
+    }
+}
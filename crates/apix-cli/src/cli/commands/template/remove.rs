@@ -0,0 +1,16 @@
+use apix_core::template::source;
+use log::{error, info};
+
+use crate::utils::internal_dir::get_internal_dir;
+
+pub fn remove_template(name: String) {
+    let internal_dir = get_internal_dir();
+
+    match source::remove_template(internal_dir.get_templates_dir(), &name) {
+        Ok(()) => info!("Removed template '{}'", name),
+        Err(e) => {
+            error!("Failed to remove template '{}': {}", name, e);
+            std::process::exit(1);
+        }
+    }
+}
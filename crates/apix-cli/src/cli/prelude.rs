@@ -2,7 +2,12 @@ use crate::{cli::cli::Commands, config::get_config, db::connect_db};
 
 pub fn pre_command_checks(command: &Commands) -> Result<(), String> {
     match command {
-        Commands::Init { .. } => Ok(()),
+        Commands::Init { .. }
+        | Commands::Adopt { .. }
+        | Commands::Doctor
+        | Commands::PluginDev { .. }
+        | Commands::Plugins { .. }
+        | Commands::Template { .. } => Ok(()),
         _ => {
             let current_dir = std::env::current_dir()
                 .map_err(|_| "Failed to get current directory".to_string())?;
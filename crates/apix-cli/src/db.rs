@@ -23,8 +23,11 @@ pub fn connect_db(db_path: &str) -> Result<(), DbConnectError> {
             DbConnectError::ConnectError(format!("create_db_and_migrate failed: {:?}", e))
         })?
     } else {
-        Db::new(db_path)
-            .map_err(|e| DbConnectError::ConnectError(format!("Db::new failed: {:?}", e)))?
+        let db = Db::new(db_path)
+            .map_err(|e| DbConnectError::ConnectError(format!("Db::new failed: {:?}", e)))?;
+        db.migrate()
+            .map_err(|e| DbConnectError::ConnectError(format!("migrate failed: {:?}", e)))?;
+        db
     };
 
     smol::block_on(async {
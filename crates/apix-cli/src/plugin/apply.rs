@@ -0,0 +1,217 @@
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use ansi_term::Colour::{Cyan, Green, Red, Yellow};
+use apix_core::{
+    monorepo::{
+        config::{MonorepoConfig, PackageConfig, ProjectConfig, UnitKind},
+        editor::MonorepoConfigEditor,
+    },
+    plugin::{
+        dependencies::LOCK_FILE,
+        plan::Plan,
+        plugin_ctx::ctx::{PluginCtx, Proposal},
+    },
+};
+use log::{error, info, warn};
+
+use crate::utils::prompt::confirm;
+
+pub const MONOREPO_FILE: &str = "monorepo.toml";
+
+/// Collects the proposals of the last plugin call into a plan. When the command
+/// is scoped to a project or package, file changes must stay inside its path
+/// and system commands run there. A changed `apix.lock` is only added to a
+/// plan with changes of the plugin, so loading a plugin never writes it.
+pub fn take_plan(ctx: &Rc<RefCell<PluginCtx>>) -> Plan {
+    try_take_plan(ctx).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Like [`take_plan`], but returns an error instead of exiting.
+pub fn try_take_plan(ctx: &Rc<RefCell<PluginCtx>>) -> Result<Plan, String> {
+    let mut plan = Plan::new(ctx.borrow_mut().proposals.drain(..).collect());
+
+    if let Some(target) = &ctx.borrow().target
+        && let Err(e) = plan.confine_to(&target.path)
+    {
+        return Err(format!(
+            "Plugin proposed a change outside {} '{}': {}",
+            target.kind.as_str(),
+            target.name,
+            e
+        ));
+    }
+
+    let lock = ctx.borrow_mut().lock.take();
+    if !plan.is_empty()
+        && let Some(content) = lock
+    {
+        let exists = match ctx.borrow().pending.get(Path::new(LOCK_FILE)) {
+            Some(content) => content.is_some(),
+            None => std::env::current_dir().unwrap().join(LOCK_FILE).is_file(),
+        };
+        let path = LOCK_FILE.to_string();
+        plan.proposals.push(if exists {
+            Proposal::ModifyFile { path, content }
+        } else {
+            Proposal::CreateFile { path, content }
+        });
+    }
+
+    Ok(plan)
+}
+
+/// Shows the plan and applies it to the monorepo. Returns `true` if changes were applied.
+pub fn review_and_apply(plan: &Plan, accept_all: bool) -> bool {
+    try_review_and_apply(plan, accept_all).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Like [`review_and_apply`], but returns an error instead of exiting.
+pub fn try_review_and_apply(plan: &Plan, accept_all: bool) -> Result<bool, String> {
+    if plan.is_empty() {
+        info!("Plugin proposed no changes");
+        return Ok(false);
+    }
+
+    let monorepo_root = std::env::current_dir().unwrap();
+
+    plan.validate(&monorepo_root)
+        .map_err(|e| format!("Plugin proposed an invalid change: {}", e))?;
+
+    println!("\nProposed changes:");
+    for line in plan.summary() {
+        let colored = match &line[..1] {
+            "A" => Green.paint(line.as_str()),
+            "M" => Yellow.paint(line.as_str()),
+            "D" => Red.paint(line.as_str()),
+            _ => Cyan.paint(line.as_str()),
+        };
+        println!("  {}", colored);
+    }
+    println!();
+
+    if !accept_all && !confirm("Apply these changes?") {
+        info!("Changes discarded");
+        return Ok(false);
+    }
+
+    plan.apply(&monorepo_root)
+        .map_err(|e| format!("Failed to apply changes, rolled back: {}", e))?;
+
+    info!("Applied {} change(s)", plan.proposals.len());
+    Ok(true)
+}
+
+pub fn record_event(ctx: &Rc<RefCell<PluginCtx>>, action: &str, args: &[String]) {
+    if let Some(history) = &ctx.borrow().history
+        && let Err(e) = history.record(action, args)
+    {
+        warn!("Failed to record '{}' event: {}", action, e);
+    }
+}
+
+/// Adds a `monorepo.toml` change registering every project and package the
+/// plugin declared as created, so it is previewed, applied and rolled back
+/// together with the plugin's files. Conflicts surface before anything is applied.
+pub fn add_registration(plan: &mut Plan, ctx: &Rc<RefCell<PluginCtx>>, plugin: &str) {
+    try_add_registration(plan, ctx, plugin).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Like [`add_registration`], but returns an error instead of exiting.
+pub fn try_add_registration(
+    plan: &mut Plan,
+    ctx: &Rc<RefCell<PluginCtx>>,
+    plugin: &str,
+) -> Result<(), String> {
+    if ctx.borrow().created.is_empty() {
+        return Ok(());
+    }
+
+    let monorepo_root = std::env::current_dir().unwrap();
+    // Registration builds on a monorepo.toml change the plugin proposed itself.
+    let proposed = plan
+        .file_changes()
+        .ok()
+        .and_then(|changes| changes.get(Path::new(MONOREPO_FILE)).cloned().flatten());
+    let editor = match proposed {
+        Some(content) => MonorepoConfigEditor::parse(monorepo_root.join(MONOREPO_FILE), &content),
+        None => MonorepoConfigEditor::open(&monorepo_root),
+    };
+    let mut editor = editor.map_err(|e| format!("Failed to open monorepo.toml: {}", e))?;
+    try_stage_registration(&mut editor, ctx, plugin)?;
+
+    plan.proposals.push(Proposal::ModifyFile {
+        path: MONOREPO_FILE.to_string(),
+        content: editor.contents(),
+    });
+    Ok(())
+}
+
+/// Adds the units the plugin declared as created to `editor` and returns the
+/// resulting config.
+pub fn stage_registration(
+    editor: &mut MonorepoConfigEditor,
+    ctx: &Rc<RefCell<PluginCtx>>,
+    plugin: &str,
+) -> MonorepoConfig {
+    try_stage_registration(editor, ctx, plugin).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Like [`stage_registration`], but returns an error instead of exiting.
+pub fn try_stage_registration(
+    editor: &mut MonorepoConfigEditor,
+    ctx: &Rc<RefCell<PluginCtx>>,
+    plugin: &str,
+) -> Result<MonorepoConfig, String> {
+    for unit in &ctx.borrow().created {
+        let result = match unit.kind {
+            UnitKind::Project => editor.add_project(
+                &unit.name,
+                &ProjectConfig {
+                    path: unit.path.clone(),
+                    language: unit.language.clone(),
+                    permissions: Vec::new(),
+                    plugin: Some(plugin.to_string()),
+                },
+            ),
+            UnitKind::Package => editor.add_package(
+                &unit.name,
+                &PackageConfig {
+                    path: unit.path.clone(),
+                    language: unit.language.clone(),
+                    permissions: Vec::new(),
+                    plugin: Some(plugin.to_string()),
+                },
+            ),
+        };
+
+        result.map_err(|e| format!("Cannot register '{}': {}", unit.name, e))?;
+    }
+
+    editor
+        .validate()
+        .map_err(|e| format!("Plugin declared an invalid project or package: {}", e))
+}
+
+/// Logs the units an applied plan registered in monorepo.toml.
+pub fn report_registration(ctx: &Rc<RefCell<PluginCtx>>) {
+    for unit in &ctx.borrow().created {
+        info!(
+            "Registered {} '{}' at '{}'",
+            unit.kind.as_str(),
+            unit.name,
+            unit.path
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use apix_core::plugin::args::{ArgSpec, ArgValues, parse_args, validate_args};
+use log::error;
+
+use crate::cli::{TargetFlags, cli::CommonFlags};
+
+/// Parses `args` of `apix plugin <plugin> <command>` against the declared
+/// `specs`, exiting with a usage error if they don't match.
+pub fn parse_declared(
+    plugin: &str,
+    command: &str,
+    specs: &[ArgSpec],
+    args: &[String],
+) -> ArgValues {
+    if let Err(e) = validate_args(specs) {
+        error!(
+            "Plugin '{}' declares invalid {} arguments: {}",
+            plugin, command, e
+        );
+        std::process::exit(1);
+    }
+
+    parse_args(format!("apix plugin {} {}", plugin, command), specs, args)
+        .unwrap_or_else(|e| e.exit())
+}
+
+/// Whether `-h` or `--help` comes before any `--`.
+pub fn wants_help(args: &[String]) -> bool {
+    args.iter()
+        .take_while(|arg| *arg != "--")
+        .any(|arg| arg == "-h" || arg == "--help")
+}
+
+/// Moves the apix flags of `extend` out of `args` into `force`, `target` and
+/// `flags`. clap leaves every flag after the first plugin argument in `args`.
+/// Everything after `--` belongs to the plugin.
+pub fn take_apix_flags(
+    args: Vec<String>,
+    force: &mut bool,
+    target: &mut TargetFlags,
+    flags: &mut CommonFlags,
+) -> Vec<String> {
+    let mut plugin_args = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--" => {
+                plugin_args.extend(args.by_ref());
+                break;
+            }
+            "--force" => *force = true,
+            "-y" | "--yes" => flags.accept_all = true,
+            "--allow-dirty" => flags.allow_dirty = true,
+            "--project" | "--package" => {
+                let Some(value) = args.next() else {
+                    error!("{} needs a value", arg);
+                    std::process::exit(1);
+                };
+                set_target(target, &arg[2..], value);
+            }
+            _ => match arg.split_once('=') {
+                Some((name @ ("--project" | "--package"), value)) => {
+                    set_target(target, &name[2..], value.to_string())
+                }
+                _ => plugin_args.push(arg),
+            },
+        }
+    }
+
+    if target.project.is_some() && target.package.is_some() {
+        error!("--project and --package cannot be used together");
+        std::process::exit(1);
+    }
+
+    plugin_args
+}
+
+fn set_target(target: &mut TargetFlags, kind: &str, name: String) {
+    match kind {
+        "project" => target.project = Some(name),
+        _ => target.package = Some(name),
+    }
+}
@@ -1,28 +1,62 @@
-use std::{cell::RefCell, rc::Rc};
+use log::warn;
 
-use apix_core::plugin::{
-    config::PluginConfig, instance::PluginInstance, plugin_ctx::ctx::PluginCtx,
-};
-
-use crate::cli::{
-    PluginCommands,
-    commands::plugin::{
-        create::call_plugin_create, extend::call_plugin_extend, info::call_plugin_info,
-        migrate::call_plugin_migrate,
+use crate::{
+    cli::{
+        PluginCommands,
+        commands::plugin::{
+            command::call_plugin_command, create::call_plugin_create, extend::call_plugin_extend,
+            info::call_plugin_info, migrate::call_plugin_migrate,
+        },
+    },
+    plugin::{
+        args::take_apix_flags,
+        helpers::{owned_targets, resolve_plugin, resolve_target},
     },
 };
 
-pub fn dispatch_plugin_command(
-    plugin: String,
-    plugin_config: PluginConfig,
-    abi: PluginInstance,
-    ctx: Rc<RefCell<PluginCtx>>,
-    command: PluginCommands,
-) {
+pub fn dispatch_plugin_command(plugin: String, command: PluginCommands) {
     match command {
-        PluginCommands::Create { name, flags } => call_plugin_create(flags, name, plugin, abi, ctx),
-        PluginCommands::Extend { args, flags } => call_plugin_extend(flags, args, abi, ctx),
-        PluginCommands::Migrate { flags } => call_plugin_migrate(flags, plugin_config, abi, ctx),
-        PluginCommands::Info => call_plugin_info(plugin, plugin_config, abi),
+        PluginCommands::Create { name, flags } => {
+            let (_, abi, ctx) = resolve_plugin(&plugin, None);
+            call_plugin_create(flags, name, plugin, abi, ctx)
+        }
+        PluginCommands::Extend {
+            mut args,
+            escaped,
+            mut force,
+            mut target,
+            mut flags,
+        } => {
+            if !escaped.is_empty() {
+                args.push("--".to_string());
+                args.extend(escaped);
+            }
+            let args = take_apix_flags(args, &mut force, &mut target, &mut flags);
+            call_plugin_extend(flags, args, force, plugin, resolve_target(target))
+        }
+        PluginCommands::Migrate {
+            all,
+            to,
+            target,
+            flags,
+        } => {
+            let targets = if all {
+                let owned = owned_targets(&plugin);
+                if owned.is_empty() {
+                    warn!("Plugin '{}' does not own any project or package", plugin);
+                    return;
+                }
+                owned.into_iter().map(Some).collect()
+            } else {
+                vec![resolve_target(target)]
+            };
+
+            call_plugin_migrate(flags, plugin, to, targets)
+        }
+        PluginCommands::Info => {
+            let (plugin_config, abi, _) = resolve_plugin(&plugin, None);
+            call_plugin_info(plugin, plugin_config, abi)
+        }
+        PluginCommands::Custom(argv) => call_plugin_command(plugin, argv),
     }
 }
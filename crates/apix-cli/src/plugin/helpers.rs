@@ -1,20 +1,102 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, path::Path, rc::Rc};
 
 use apix_core::{
-    monorepo::config::get_monorepo_config,
+    events::EventHistory,
+    monorepo::config::{MonorepoTarget, UnitKind, get_monorepo_config},
     plugin::{
-        config::{PluginConfig, get_plugin_config},
+        config::{PluginConfig, get_plugin_config, installed_versions, read_plugin_config},
+        dependencies::{LOCK_FILE, Lockfile},
+        file_tree::PendingFiles,
         instance::PluginInstance,
         plugin_ctx::ctx::PluginCtx,
-        utils::load_plugin,
+        utils::load_plugin_with_ctx,
     },
     utils::version::{VersionCheck, check_plugin_version},
 };
-use log::error;
+use log::{error, info};
+use semver::Version;
 
-use crate::utils::internal_dir::get_internal_dir;
+use crate::{cli::TargetFlags, db::get_db, utils::internal_dir::get_internal_dir};
 
-pub fn resolve_plugin(plugin: &str) -> (PluginConfig, PluginInstance, Rc<RefCell<PluginCtx>>) {
+pub fn resolve_plugin(
+    plugin: &str,
+    target: Option<MonorepoTarget>,
+) -> (PluginConfig, PluginInstance, Rc<RefCell<PluginCtx>>) {
+    resolve_plugin_with_pending(plugin, target, PendingFiles::new())
+}
+
+/// Like [`resolve_plugin`], with `pending` changes of earlier recipe steps
+/// visible to the plugin as if they were already applied.
+pub fn resolve_plugin_with_pending(
+    plugin: &str,
+    target: Option<MonorepoTarget>,
+    pending: PendingFiles,
+) -> (PluginConfig, PluginInstance, Rc<RefCell<PluginCtx>>) {
+    let plugin_config = registered_plugin_config(plugin);
+    let (abi, ctx) = load_resolved_plugin(plugin, &plugin_config, target, pending);
+
+    (plugin_config, abi, ctx)
+}
+
+/// Loads the version of `plugin` that `plugin_config` was read from.
+pub fn load_resolved_plugin(
+    plugin: &str,
+    plugin_config: &PluginConfig,
+    target: Option<MonorepoTarget>,
+    pending: PendingFiles,
+) -> (PluginInstance, Rc<RefCell<PluginCtx>>) {
+    try_load_resolved_plugin(plugin, plugin_config, target, pending).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Like [`load_resolved_plugin`], returning the error instead of exiting.
+pub fn try_load_resolved_plugin(
+    plugin: &str,
+    plugin_config: &PluginConfig,
+    target: Option<MonorepoTarget>,
+    pending: PendingFiles,
+) -> Result<(PluginInstance, Rc<RefCell<PluginCtx>>), String> {
+    let monorepo_root = std::env::current_dir().unwrap();
+    let binding = get_internal_dir();
+    let plugins_dir = binding.get_plugins_dir();
+
+    let (project, package) = match &target {
+        Some(t) if t.kind == UnitKind::Project => (Some(t.name.clone()), None),
+        Some(t) => (None, Some(t.name.clone())),
+        None => (None, None),
+    };
+
+    let ctx = Rc::new(RefCell::new(PluginCtx::new(plugin)));
+    ctx.borrow_mut().target = target;
+    ctx.borrow_mut().pending = pending;
+
+    let abi = load_plugin_with_ctx(
+        plugin,
+        &plugin_config.version,
+        &monorepo_root,
+        plugins_dir,
+        ctx.clone(),
+    )
+    .map_err(|e| format!("Failed to load plugin '{}': {}", plugin, e))?;
+
+    lock_dependencies(&monorepo_root, plugin, &abi, &ctx)?;
+
+    let history = EventHistory::new(get_db(), plugin, &plugin_config.version, project, package)
+        .map_err(|e| {
+            format!(
+                "Failed to read event history for plugin '{}': {}",
+                plugin, e
+            )
+        })?;
+    ctx.borrow_mut().history = Some(history);
+
+    Ok((abi, ctx))
+}
+
+/// plugin.toml of the installed version of `plugin` that monorepo.toml requires.
+pub fn registered_plugin_config(plugin: &str) -> PluginConfig {
     let monorepo_root = std::env::current_dir().unwrap();
     let monorepo_config = get_monorepo_config(&monorepo_root).unwrap_or_else(|e| {
         error!("Error reading monorepo config: {}", e);
@@ -33,7 +115,7 @@ pub fn resolve_plugin(plugin: &str) -> (PluginConfig, PluginInstance, Rc<RefCell
     let binding = get_internal_dir();
     let plugins_dir = binding.get_plugins_dir();
     let (plugin_config, resolved_version) =
-        get_plugin_config(&plugins_dir, plugin, &required_version).unwrap_or_else(|e| {
+        get_plugin_config(plugins_dir, plugin, required_version).unwrap_or_else(|e| {
             error!("Error reading plugin config: {}", e);
             std::process::exit(1);
         });
@@ -42,16 +124,148 @@ pub fn resolve_plugin(plugin: &str) -> (PluginConfig, PluginInstance, Rc<RefCell
         plugin,
         &plugin_config.version,
         &resolved_version,
-        &required_version,
+        required_version,
     );
 
-    let (abi, ctx) = load_plugin(plugin, &plugin_config.version, &monorepo_root, &plugins_dir)
+    plugin_config
+}
+
+/// plugin.toml of the version to migrate `plugin` to, `to` or else the newest
+/// installed one, and the version monorepo.toml requires now. `None` if that
+/// version is not newer than the required one, which is logged.
+pub fn migration_plugin_config(plugin: &str, to: Option<&str>) -> Option<(PluginConfig, String)> {
+    let monorepo_root = std::env::current_dir().unwrap();
+    let monorepo_config = get_monorepo_config(&monorepo_root).unwrap_or_else(|e| {
+        error!("Error reading monorepo config: {}", e);
+        std::process::exit(1);
+    });
+
+    let Some(from_version) = monorepo_config.plugins.get(plugin).map(|v| v.version()) else {
+        error!("Plugin '{}' not registered in monorepo.toml", plugin);
+        std::process::exit(1);
+    };
+    let Ok(from) = Version::parse(from_version) else {
+        error!(
+            "Plugin '{}' requires '{}' in monorepo.toml, which is not a version to migrate from",
+            plugin, from_version
+        );
+        std::process::exit(1);
+    };
+
+    let binding = get_internal_dir();
+    let plugins_dir = binding.get_plugins_dir();
+    let installed = installed_versions(plugins_dir, plugin).unwrap_or_default();
+
+    let version = match to {
+        Some(to) => {
+            let Ok(version) = Version::parse(to) else {
+                error!("Invalid version string '{}'", to);
+                std::process::exit(1);
+            };
+            if !installed.contains(&version) {
+                error!("Plugin '{}' v{} is not installed", plugin, version);
+                std::process::exit(1);
+            }
+            version
+        }
+        None => match installed.last() {
+            Some(version) => version.clone(),
+            None => {
+                error!("No installed versions found for plugin '{}'", plugin);
+                std::process::exit(1);
+            }
+        },
+    };
+
+    if version <= from {
+        info!(
+            "'{}' is at v{} in monorepo.toml, which is not older than v{}, nothing to migrate",
+            plugin, from, version
+        );
+        return None;
+    }
+
+    let plugin_config = read_plugin_config(&plugins_dir.join(plugin).join(version.to_string()))
         .unwrap_or_else(|e| {
-            error!("Failed to load plugin '{}': {}", plugin, e);
+            error!("Error reading plugin config: {}", e);
             std::process::exit(1);
         });
+    validate_plugin_versions(
+        plugin,
+        &plugin_config.version,
+        &version.to_string(),
+        &version.to_string(),
+    );
 
-    (plugin_config, abi, ctx)
+    Some((plugin_config, from_version.to_string()))
+}
+
+/// Stages the dependency versions the plugin loaded with for `apix.lock`,
+/// see [`take_plan`](crate::plugin::apply::take_plan).
+fn lock_dependencies(
+    monorepo_root: &Path,
+    plugin: &str,
+    abi: &PluginInstance,
+    ctx: &Rc<RefCell<PluginCtx>>,
+) -> Result<(), String> {
+    let mut ctx = ctx.borrow_mut();
+    // Earlier recipe steps may have changed the lock already.
+    let lock = match ctx.pending.get(Path::new(LOCK_FILE)) {
+        Some(Some(content)) => Lockfile::parse(content),
+        Some(None) => Ok(Lockfile::default()),
+        None => Lockfile::load(monorepo_root),
+    };
+    let result = lock.and_then(|mut lock| {
+        if lock.update(plugin, abi.dependencies().clone()) {
+            Ok(Some(lock.contents()?))
+        } else {
+            Ok(None)
+        }
+    });
+
+    ctx.lock = result.map_err(|e| format!("Failed to update {}: {}", LOCK_FILE, e))?;
+
+    Ok(())
+}
+
+/// Resolves `--project`/`--package` against monorepo.toml.
+pub fn resolve_target(TargetFlags { project, package }: TargetFlags) -> Option<MonorepoTarget> {
+    if project.is_none() && package.is_none() {
+        return None;
+    }
+
+    let monorepo_root = std::env::current_dir().unwrap();
+    let monorepo_config = get_monorepo_config(&monorepo_root).unwrap_or_else(|e| {
+        error!("Error reading monorepo config: {}", e);
+        std::process::exit(1);
+    });
+
+    let target = match (&project, &package) {
+        (Some(name), _) => monorepo_config.project_target(name),
+        (_, Some(name)) => monorepo_config.package_target(name),
+        (None, None) => None,
+    };
+
+    if target.is_none() {
+        match (project, package) {
+            (Some(name), _) => error!("Project '{}' not registered in monorepo.toml", name),
+            (_, Some(name)) => error!("Package '{}' not registered in monorepo.toml", name),
+            (None, None) => {}
+        }
+        std::process::exit(1);
+    }
+
+    target
+}
+
+pub fn owned_targets(plugin: &str) -> Vec<MonorepoTarget> {
+    let monorepo_root = std::env::current_dir().unwrap();
+    let monorepo_config = get_monorepo_config(&monorepo_root).unwrap_or_else(|e| {
+        error!("Error reading monorepo config: {}", e);
+        std::process::exit(1);
+    });
+
+    monorepo_config.targets_owned_by(plugin)
 }
 
 pub fn validate_plugin_versions(
@@ -60,33 +274,49 @@ pub fn validate_plugin_versions(
     resolved_version: &str,
     required_version: &str,
 ) {
+    if let Err(e) =
+        check_plugin_versions(plugin, config_version, resolved_version, required_version)
+    {
+        error!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Like [`validate_plugin_versions`], returning the error instead of exiting.
+pub fn check_plugin_versions(
+    plugin: &str,
+    config_version: &str,
+    resolved_version: &str,
+    required_version: &str,
+) -> Result<(), String> {
     match check_plugin_version(resolved_version, required_version) {
         Ok(VersionCheck::UpToDate) => {}
         Ok(VersionCheck::PluginNewer) => {
-            error!(
+            return Err(format!(
                 "Installed plugin '{}' (v{}) is newer than required '{}'",
                 plugin, resolved_version, required_version
-            );
-            std::process::exit(1);
+            ));
         }
         Ok(VersionCheck::PluginOutdated) => {
-            error!(
+            return Err(format!(
                 "Installed plugin '{}' (v{}) is older than required '{}'",
                 plugin, resolved_version, required_version
-            );
-            std::process::exit(1);
+            ));
         }
         Err(e) => {
-            error!("Failed to compare versions for plugin '{}': {}", plugin, e);
-            std::process::exit(1);
+            return Err(format!(
+                "Failed to compare versions for plugin '{}': {}",
+                plugin, e
+            ));
         }
     }
 
     if config_version != resolved_version {
-        error!(
+        return Err(format!(
             "Plugin '{}' folder name version '{}' does not match plugin.toml '{}'",
             plugin, resolved_version, config_version
-        );
-        std::process::exit(1);
+        ));
     }
+
+    Ok(())
 }
@@ -0,0 +1,116 @@
+use apix_core::{
+    monorepo::config::get_monorepo_config,
+    plugin::{
+        config::get_plugin_config,
+        file_tree::PendingFiles,
+        hooks::{AppliedChanges, triggered_hooks},
+    },
+};
+use log::{error, info, warn};
+
+use crate::{
+    plugin::{
+        apply::{
+            record_event, report_registration, try_add_registration, try_review_and_apply,
+            try_take_plan,
+        },
+        helpers::{check_plugin_versions, try_load_resolved_plugin},
+    },
+    utils::internal_dir::get_internal_dir,
+};
+
+/// Calls the hooks registered plugins subscribed to for `changes` and offers
+/// each follow-up plan on its own. Follow-up plans do not trigger hooks again.
+pub fn run_hooks(changes: &AppliedChanges, accept_all: bool) {
+    if changes.is_empty() {
+        return;
+    }
+
+    let monorepo_root = std::env::current_dir().unwrap();
+    let monorepo_config = get_monorepo_config(&monorepo_root).unwrap_or_else(|e| {
+        error!("Error reading monorepo config: {}", e);
+        std::process::exit(1);
+    });
+    let binding = get_internal_dir();
+    let plugins_dir = binding.get_plugins_dir();
+
+    let mut plugins: Vec<_> = monorepo_config.plugins.iter().collect();
+    plugins.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (plugin, meta) in plugins {
+        // Plugins that are not installed are reported by `apix doctor`.
+        let Ok((plugin_config, resolved_version)) =
+            get_plugin_config(plugins_dir, plugin, meta.version())
+        else {
+            continue;
+        };
+        let events = triggered_hooks(&plugin_config, changes);
+        if events.is_empty() {
+            continue;
+        }
+
+        // The triggering changes are already applied, a broken subscriber
+        // must not abort the command.
+        let loaded = check_plugin_versions(
+            plugin,
+            &plugin_config.version,
+            &resolved_version,
+            meta.version(),
+        )
+        .and_then(|_| try_load_resolved_plugin(plugin, &plugin_config, None, PendingFiles::new()));
+        let (abi, ctx) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                warn!("Skipping hooks of '{}': {}", plugin, e);
+                continue;
+            }
+        };
+        for event in events {
+            // Drop what an earlier hook call of this plugin left behind.
+            ctx.borrow_mut().proposals.clear();
+            ctx.borrow_mut().created.clear();
+
+            info!("Running '{}' hook of '{}'", event.name, plugin);
+            match abi.hook(&event) {
+                Ok(0) => {}
+                Ok(code) => {
+                    warn!(
+                        "Hook '{}' of '{}' exited with code {}",
+                        event.name, plugin, code
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Hook '{}' of '{}' failed: {}", event.name, plugin, e);
+                    continue;
+                }
+            }
+
+            let plan = try_take_plan(&ctx).and_then(|mut plan| {
+                try_add_registration(&mut plan, &ctx, plugin)?;
+                Ok(plan)
+            });
+            let plan = match plan {
+                Ok(plan) if plan.is_empty() => continue,
+                Ok(plan) => plan,
+                Err(e) => {
+                    warn!("Hook '{}' of '{}': {}", event.name, plugin, e);
+                    continue;
+                }
+            };
+
+            info!(
+                "'{}' proposes follow-up changes for '{}'",
+                plugin, event.name
+            );
+            match try_review_and_apply(&plan, accept_all) {
+                Ok(true) => {
+                    report_registration(&ctx);
+                    record_event(&ctx, "hook", std::slice::from_ref(&event.name));
+                }
+                Ok(false) => {}
+                Err(e) => warn!("Hook '{}' of '{}': {}", event.name, plugin, e),
+            }
+        }
+    }
+}
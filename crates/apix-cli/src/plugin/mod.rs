@@ -1,2 +1,5 @@
+pub mod apply;
+pub mod args;
 pub mod dispatcher;
 pub mod helpers;
+pub mod hooks;
@@ -1,4 +1,4 @@
-use std::process::Command as ProcCommand;
+use std::{path::Path, process::Command as ProcCommand};
 
 use log::error;
 
@@ -25,3 +25,26 @@ pub fn ensure_clean_tree(allow_dirty: bool) {
         std::process::exit(1);
     }
 }
+
+pub fn init_repo(path: &Path) -> Result<(), String> {
+    run_git(path, &["init", "--quiet"])
+}
+
+pub fn commit_all(path: &Path, message: &str) -> Result<(), String> {
+    run_git(path, &["add", "--all"])?;
+    run_git(path, &["commit", "--quiet", "-m", message])
+}
+
+fn run_git(path: &Path, args: &[&str]) -> Result<(), String> {
+    let status = ProcCommand::new("git")
+        .args(args)
+        .current_dir(path)
+        .status()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !status.success() {
+        return Err(format!("'git {}' failed with {}", args.join(" "), status));
+    }
+
+    Ok(())
+}
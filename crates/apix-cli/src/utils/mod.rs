@@ -1,2 +1,3 @@
 pub mod git;
 pub mod internal_dir;
+pub mod prompt;
@@ -0,0 +1,32 @@
+use std::io::{self, Write};
+
+pub fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        return false;
+    }
+
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Asks for a line of input. An empty answer yields `default`, if any.
+pub fn ask(question: &str, default: Option<&str>) -> String {
+    match default {
+        Some(default) => print!("{} [{}]: ", question, default),
+        None => print!("{}: ", question),
+    }
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        return default.unwrap_or_default().to_string();
+    }
+
+    match input.trim() {
+        "" => default.unwrap_or_default().to_string(),
+        answer => answer.to_string(),
+    }
+}
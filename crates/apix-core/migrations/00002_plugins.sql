@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS plugins (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    timestamp TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1 @@
+ALTER TABLE plugins ADD COLUMN version TEXT;
@@ -0,0 +1,7 @@
+DELETE FROM events WHERE id NOT IN (
+    SELECT MAX(id) FROM events
+    GROUP BY plugin, COALESCE(project, ''), COALESCE(package, ''), action, COALESCE(args, '')
+);
+CREATE UNIQUE INDEX IF NOT EXISTS events_key ON events (
+    plugin, COALESCE(project, ''), COALESCE(package, ''), action, COALESCE(args, '')
+);
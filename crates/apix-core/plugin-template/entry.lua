@@ -0,0 +1,47 @@
+--- Scaffolds a new project called `project_name`.
+---@param project_name string
+---@return integer exit code, 0 on success
+function create(project_name)
+  ctx.info("Creating " .. project_name)
+
+  ctx.create_file(project_name .. "/README.md", "# " .. project_name .. "\n")
+
+  ctx.declare_created({
+    name = project_name,
+    kind = "project",
+    path = project_name,
+    language = "unknown",
+  })
+
+  return 0
+end
+
+--- Adds features to an existing project.
+---@param args string[] arguments after `apix plugin {{name}} extend`, or a table of
+--- parsed values when `[extend]` or `info().args` declares them
+---@return integer exit code, 0 on success
+function extend(args)
+  return 0
+end
+
+--- Upgrades projects created by an older version of this plugin.
+---@param from_version string version the project was last touched with
+---@return integer exit code, 0 on success
+function migrate(from_version)
+  return 0
+end
+
+--- Help shown by `apix plugin {{name}} info`.
+---@return { usage: string[]?, options: string[][]?, args: table[]? }
+function info()
+  return {
+    usage = {
+      "apix plugin {{name}} create <name>",
+      "apix plugin {{name}} extend [args...]",
+      "apix plugin {{name}} migrate",
+    },
+    options = {
+      { "-y, --yes", "Accept changes" },
+    },
+  }
+end
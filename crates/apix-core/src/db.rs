@@ -1,6 +1,7 @@
 use libsql::{Builder, Connection, Value};
 use smol::{fs, stream::StreamExt};
 use std::{
+    collections::HashMap,
     error::Error,
     path::{Path, PathBuf},
 };
@@ -27,44 +28,89 @@ impl Db {
     }
 
     pub fn create_db_and_migrate(db_path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        Ok(smol::block_on(async {
+        smol::block_on(async {
             let apix_dir = db_path
                 .parent()
-                .ok_or("Invalid DB path: no parent directory")
-                .unwrap();
+                .ok_or("Invalid DB path: no parent directory")?;
             if fs::metadata(&apix_dir).await.is_err() {
-                fs::create_dir_all(&apix_dir).await.unwrap();
+                fs::create_dir_all(&apix_dir).await?;
             }
 
-            let db = Builder::new_local(db_path)
-                .build()
-                .await
-                .expect("Failed to connect to DB");
-            let conn = db.connect().unwrap();
+            let db = Builder::new_local(db_path).build().await?;
+            let conn = db.connect()?;
 
-            let mut migrations_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-            migrations_path.push("migrations");
+            let db = Self { conn };
+            db.apply_migrations().await?;
 
-            let mut entries = fs::read_dir(&migrations_path).await.unwrap();
-            let mut files = Vec::new();
+            Ok(db)
+        })
+    }
 
-            while let Some(entry) = entries.next().await {
-                let entry = entry.unwrap();
-                let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "sql") {
-                    files.push(path);
-                }
-            }
+    /// Applies migrations newer than the DB's schema version. Older databases
+    /// only ran the migrations that existed when they were created.
+    pub fn migrate(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        smol::block_on(self.apply_migrations())
+    }
 
-            files.sort_by_key(|p| p.file_name().map(|n| n.to_os_string()));
+    /// Schema version the DB is at, i.e. the number of applied migrations.
+    pub fn schema_version(&self) -> Result<u32, Box<dyn Error + Send + Sync>> {
+        smol::block_on(self.read_schema_version())
+    }
 
-            for file_path in files {
-                let sql = fs::read_to_string(&file_path).await.unwrap();
-                conn.execute_batch(&sql).await.unwrap();
+    /// Schema version a fully migrated DB has.
+    pub fn latest_schema_version() -> Result<u32, Box<dyn Error + Send + Sync>> {
+        smol::block_on(async { Ok(migration_files().await?.len() as u32) })
+    }
+
+    async fn read_schema_version(&self) -> Result<u32, Box<dyn Error + Send + Sync>> {
+        let mut rows = self.conn.query("PRAGMA user_version;", ()).await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(row.get::<u32>(0)?),
+            None => Ok(0),
+        }
+    }
+
+    async fn apply_migrations(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let current = self.read_schema_version().await? as usize;
+
+        for (index, file_path) in migration_files().await?.into_iter().enumerate() {
+            if index < current {
+                continue;
             }
 
-            Self { conn }
-        }))
+            let sql = fs::read_to_string(&file_path).await?;
+            self.conn
+                .execute_batch(&format!("{}\nPRAGMA user_version = {};", sql, index + 1))
+                .await
+                .map_err(|e| format!("Migration {:?} failed: {}", file_path.file_name(), e))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn plugin_id(&self, name: &str) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        smol::block_on(async {
+            self.conn
+                .execute(
+                    "INSERT OR IGNORE INTO plugins (name) VALUES (?1);",
+                    libsql::params![name],
+                )
+                .await?;
+
+            let mut rows = self
+                .conn
+                .query(
+                    "SELECT id FROM plugins WHERE name=?1;",
+                    libsql::params![name],
+                )
+                .await?;
+
+            match rows.next().await? {
+                Some(row) => Ok(row.get::<u64>(0)?),
+                None => Err(format!("Plugin '{}' could not be registered", name).into()),
+            }
+        })
     }
 
     pub fn event_exists(
@@ -77,7 +123,7 @@ impl Db {
     ) -> Result<bool, Box<dyn Error + Send + Sync>> {
         smol::block_on(async {
             let mut rows = self.conn.query(
-                "SELECT COUNT(*) as cnt FROM events WHERE plugin=?1 AND project=?2 AND package=?3 AND action=?4 AND args=?5;",
+                "SELECT COUNT(*) as cnt FROM events WHERE plugin=?1 AND project IS ?2 AND package IS ?3 AND action=?4 AND args=?5;",
                 libsql::params![plugin, project, package, action, args],
             ).await?;
 
@@ -100,7 +146,9 @@ impl Db {
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         smol::block_on(async {
             self.conn.execute(
-                "INSERT INTO events (plugin, project, package, action, args) VALUES (?1, ?2, ?3, ?4, ?5);",
+                "INSERT INTO events (plugin, project, package, action, args) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (plugin, COALESCE(project, ''), COALESCE(package, ''), action, COALESCE(args, ''))
+                 DO UPDATE SET timestamp = datetime('now');",
                 libsql::params![
                     plugin,
                     project,
@@ -113,4 +161,102 @@ impl Db {
             Ok(())
         })
     }
+
+    /// Remembers the plugin version the last recorded event was applied with.
+    pub fn set_plugin_version(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        smol::block_on(async {
+            self.conn
+                .execute(
+                    "UPDATE plugins SET version=?2 WHERE name=?1;",
+                    libsql::params![name, version],
+                )
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    /// Last applied version of every plugin that recorded an event.
+    pub fn plugin_versions(&self) -> Result<HashMap<String, String>, Box<dyn Error + Send + Sync>> {
+        smol::block_on(async {
+            let mut rows = self
+                .conn
+                .query(
+                    "SELECT name, version FROM plugins WHERE version IS NOT NULL;",
+                    (),
+                )
+                .await?;
+
+            let mut versions = HashMap::new();
+            while let Some(row) = rows.next().await? {
+                versions.insert(row.get::<String>(0)?, row.get::<String>(1)?);
+            }
+
+            Ok(versions)
+        })
+    }
+
+    /// Most recent event for every project and package.
+    pub fn last_events(&self) -> Result<Vec<EventRecord>, Box<dyn Error + Send + Sync>> {
+        smol::block_on(async {
+            let mut rows = self
+                .conn
+                .query(
+                    "SELECT p.name, e.project, e.package, e.action, e.args, MAX(e.timestamp)
+                     FROM events e JOIN plugins p ON p.id = e.plugin
+                     GROUP BY e.project, e.package;",
+                    (),
+                )
+                .await?;
+
+            let mut events = Vec::new();
+            while let Some(row) = rows.next().await? {
+                events.push(EventRecord {
+                    plugin: row.get(0)?,
+                    project: row.get(1)?,
+                    package: row.get(2)?,
+                    action: row.get(3)?,
+                    args: row.get::<Option<String>>(4)?.unwrap_or_default(),
+                    timestamp: row.get(5)?,
+                });
+            }
+
+            Ok(events)
+        })
+    }
+}
+
+/// A row of the `events` table with the plugin resolved to its name.
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    pub plugin: String,
+    pub project: Option<String>,
+    pub package: Option<String>,
+    pub action: String,
+    pub args: String,
+    pub timestamp: String,
+}
+
+/// SQL files in the bundled `migrations` folder, in the order they apply.
+async fn migration_files() -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let mut migrations_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    migrations_path.push("migrations");
+
+    let mut entries = fs::read_dir(&migrations_path).await?;
+    let mut files = Vec::new();
+
+    while let Some(entry) = entries.next().await {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "sql") {
+            files.push(path);
+        }
+    }
+
+    files.sort_by_key(|p| p.file_name().map(|n| n.to_os_string()));
+
+    Ok(files)
 }
@@ -1,6 +1,10 @@
+use libsql::Value;
 use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, sync::Arc};
 use uuid::Uuid;
 
+use crate::db::Db;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Event {
     pub id: String,
@@ -21,3 +25,80 @@ impl Event {
         }
     }
 }
+
+/// Serializes action arguments the same way for lookups and inserts,
+/// so identical invocations always map to the same `events` row.
+pub fn serialize_args(args: &[String]) -> String {
+    serde_json::to_string(args).unwrap_or_default()
+}
+
+/// Events already recorded for one plugin on one project/package.
+pub struct EventHistory {
+    db: Arc<Db>,
+    plugin_id: u64,
+    plugin: String,
+    version: String,
+    pub project: Option<String>,
+    pub package: Option<String>,
+}
+
+impl EventHistory {
+    pub fn new(
+        db: Arc<Db>,
+        plugin: &str,
+        version: &str,
+        project: Option<String>,
+        package: Option<String>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let plugin_id = db.plugin_id(plugin)?;
+
+        Ok(Self {
+            db,
+            plugin_id,
+            plugin: plugin.to_string(),
+            version: version.to_string(),
+            project,
+            package,
+        })
+    }
+
+    pub fn contains(
+        &self,
+        action: &str,
+        args: &[String],
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        self.db.event_exists(
+            &self.plugin_id,
+            self.project.clone(),
+            self.package.clone(),
+            action,
+            &serialize_args(args),
+        )
+    }
+
+    pub fn record(
+        &self,
+        action: &str,
+        args: &[String],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.db.insert_event(
+            Value::Integer(self.plugin_id as i64),
+            self.project.clone(),
+            self.package.clone(),
+            action,
+            &serialize_args(args),
+        )?;
+        self.db.set_plugin_version(&self.plugin, &self.version)
+    }
+}
+
+impl fmt::Debug for EventHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventHistory")
+            .field("plugin_id", &self.plugin_id)
+            .field("version", &self.version)
+            .field("project", &self.project)
+            .field("package", &self.package)
+            .finish()
+    }
+}
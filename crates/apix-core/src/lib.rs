@@ -2,4 +2,5 @@ pub mod db;
 pub mod events;
 pub mod monorepo;
 pub mod plugin;
+pub mod template;
 pub mod utils;
@@ -0,0 +1,303 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::warn;
+
+use crate::monorepo::{
+    config::{PackageConfig, ProjectConfig, UnitKind},
+    editor::MonorepoConfigEditor,
+};
+
+/// Folders that never contain units of their own.
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "out",
+    "vendor",
+    "venv",
+    "__pycache__",
+];
+
+/// A project or package found by [`detect_units`].
+#[derive(Debug, Clone)]
+pub struct DetectedUnit {
+    pub name: String,
+    pub kind: UnitKind,
+    /// Path relative to the scanned root, using `/` separators.
+    pub path: String,
+    pub language: String,
+    /// Marker file the unit was inferred from.
+    pub marker: String,
+}
+
+/// Walks `root` looking for project markers. The root itself and anything
+/// below a detected unit are not reported, so paths never overlap.
+pub fn detect_units(root: &Path) -> Result<Vec<DetectedUnit>, Box<dyn Error>> {
+    let mut units = Vec::new();
+    scan_dir(root, root, &mut units)?;
+
+    units.sort_by(|a, b| a.path.cmp(&b.path));
+    dedupe_names(&mut units);
+
+    Ok(units)
+}
+
+/// Builds the content of a fresh `monorepo.toml` registering `units`.
+pub fn render_monorepo_config(
+    monorepo_root: &Path,
+    repo_name: &str,
+    units: &[DetectedUnit],
+) -> Result<String, Box<dyn Error>> {
+    let base = format!(
+        "# Monorepo managed by apix\n\n[repo]\nname = {}\nversion = \"0.1.0\"\ntemplate = \"adopted\"\n\n[projects]\n\n[packages]\n\n[plugins]\n",
+        toml_edit::Value::from(repo_name)
+    );
+    let mut editor = MonorepoConfigEditor::parse(monorepo_root.join("monorepo.toml"), &base)?;
+
+    for unit in units {
+        match unit.kind {
+            UnitKind::Project => editor.add_project(
+                &unit.name,
+                &ProjectConfig {
+                    path: unit.path.clone(),
+                    language: unit.language.clone(),
+                    permissions: Vec::new(),
+                    plugin: None,
+                },
+            )?,
+            UnitKind::Package => editor.add_package(
+                &unit.name,
+                &PackageConfig {
+                    path: unit.path.clone(),
+                    language: unit.language.clone(),
+                    permissions: Vec::new(),
+                    plugin: None,
+                },
+            )?,
+        }
+    }
+
+    editor.validate()?;
+
+    Ok(editor.contents())
+}
+
+fn scan_dir(root: &Path, dir: &Path, units: &mut Vec<DetectedUnit>) -> Result<(), Box<dyn Error>> {
+    if dir != root
+        && let Some(unit) = detect_unit(root, dir)?
+    {
+        units.push(unit);
+        return Ok(());
+    }
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for entry in entries {
+        let name = entry
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if name.starts_with('.')
+            || name.starts_with("bazel-")
+            || SKIPPED_DIRS.contains(&name.as_str())
+        {
+            continue;
+        }
+
+        scan_dir(root, &entry, units)?;
+    }
+
+    Ok(())
+}
+
+/// Checks the markers of a single folder, in order of precedence.
+fn detect_unit(root: &Path, dir: &Path) -> Result<Option<DetectedUnit>, Box<dyn Error>> {
+    let rel = dir
+        .strip_prefix(root)?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/");
+    let dir_name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let unit = |name: Option<String>, kind: UnitKind, language: &str, marker: &str| {
+        Some(DetectedUnit {
+            name: name.unwrap_or_else(|| dir_name.clone()),
+            kind,
+            path: rel.clone(),
+            language: language.to_string(),
+            marker: marker.to_string(),
+        })
+    };
+
+    let cargo = dir.join("Cargo.toml");
+    if let Some(manifest) = read_manifest(&cargo, |c| toml::from_str::<toml::Table>(c).ok()) {
+        // Virtual workspace manifests only group other crates.
+        if let Some(package) = manifest.get("package") {
+            let name = package
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map(String::from);
+            let is_bin = dir.join("src/main.rs").is_file() || manifest.contains_key("bin");
+
+            return Ok(unit(name, kind_for(&rel, is_bin), "rust", "Cargo.toml"));
+        }
+    }
+
+    let package_json = dir.join("package.json");
+    if let Some(manifest) = read_manifest(&package_json, |c| {
+        serde_json::from_str::<serde_json::Value>(c).ok()
+    }) && manifest.get("workspaces").is_none()
+    {
+        let name = manifest
+            .get("name")
+            .and_then(|n| n.as_str())
+            .map(|n| n.rsplit('/').next().unwrap_or(n).to_string());
+        let language = if dir.join("tsconfig.json").is_file() {
+            "typescript"
+        } else {
+            "javascript"
+        };
+        let is_app = manifest.get("bin").is_some()
+            || manifest.pointer("/scripts/start").is_some()
+            || manifest.pointer("/scripts/dev").is_some();
+
+        return Ok(unit(name, kind_for(&rel, is_app), language, "package.json"));
+    }
+
+    let go_mod = dir.join("go.mod");
+    if go_mod.is_file() {
+        let name = fs::read_to_string(&go_mod)?
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))
+            .map(|module| {
+                module
+                    .trim()
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(module)
+                    .to_string()
+            });
+        let is_main = dir.join("main.go").is_file() || dir.join("cmd").is_dir();
+
+        return Ok(unit(name, kind_for(&rel, is_main), "go", "go.mod"));
+    }
+
+    let pyproject = dir.join("pyproject.toml");
+    if let Some(manifest) = read_manifest(&pyproject, |c| toml::from_str::<toml::Table>(c).ok()) {
+        let project = manifest.get("project");
+        let name = project
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .map(String::from);
+        let has_scripts = project.and_then(|p| p.get("scripts")).is_some();
+
+        return Ok(unit(
+            name,
+            kind_for(&rel, has_scripts),
+            "python",
+            "pyproject.toml",
+        ));
+    }
+
+    for marker in ["BUILD.bazel", "BUILD"] {
+        let build = dir.join(marker);
+        if build.is_file()
+            && let Some((language, is_bin)) = bazel_rules(&fs::read_to_string(&build)?)
+        {
+            return Ok(unit(None, kind_for(&rel, is_bin), language, marker));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Parses the manifest at `path` if there is one. Unreadable or invalid
+/// manifests are skipped with a warning instead of failing the whole scan.
+fn read_manifest<T>(path: &Path, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+    if !path.is_file() {
+        return None;
+    }
+
+    let manifest = fs::read_to_string(path).ok().and_then(|c| parse(&c));
+    if manifest.is_none() {
+        warn!("Skipping {}, it cannot be parsed", path.display());
+    }
+
+    manifest
+}
+
+/// Infers the language and whether a BUILD file defines a binary from the
+/// first `<lang>_binary` / `<lang>_library` rule it contains.
+fn bazel_rules(content: &str) -> Option<(&'static str, bool)> {
+    const LANGUAGES: &[(&str, &str)] = &[
+        ("rust_", "rust"),
+        ("go_", "go"),
+        ("py_", "python"),
+        ("cc_", "cpp"),
+        ("java_", "java"),
+        ("kt_jvm_", "kotlin"),
+        ("ts_", "typescript"),
+        ("js_", "javascript"),
+    ];
+
+    let mut found = None;
+    for line in content.lines() {
+        let rule = line.trim_start();
+        for (prefix, language) in LANGUAGES {
+            let Some(rest) = rule.strip_prefix(prefix) else {
+                continue;
+            };
+            if rest.starts_with("binary(") {
+                return Some((*language, true));
+            }
+            if rest.starts_with("library(") && found.is_none() {
+                found = Some((*language, false));
+            }
+        }
+    }
+
+    found
+}
+
+/// Conventional `apps/` and `packages/`-style folders win over what the
+/// manifest suggests.
+fn kind_for(rel: &str, looks_like_project: bool) -> UnitKind {
+    match rel.split('/').next() {
+        Some("apps" | "services" | "cmd") => UnitKind::Project,
+        Some("packages" | "libs" | "crates" | "pkg") => UnitKind::Package,
+        _ if looks_like_project => UnitKind::Project,
+        _ => UnitKind::Package,
+    }
+}
+
+/// Units with clashing names are renamed after their path.
+fn dedupe_names(units: &mut [DetectedUnit]) {
+    let mut seen = HashSet::new();
+    let duplicates: HashSet<String> = units
+        .iter()
+        .filter(|u| !seen.insert(u.name.clone()))
+        .map(|u| u.name.clone())
+        .collect();
+
+    for unit in units.iter_mut() {
+        if duplicates.contains(&unit.name) {
+            unit.name = unit.path.replace('/', "-");
+        }
+    }
+}
@@ -1,12 +1,20 @@
+use semver::Version;
 use serde::Deserialize;
 use std::{collections::HashMap, fs, path::PathBuf};
 
+use crate::monorepo::recipe::Recipe;
+
 #[derive(Debug, Deserialize)]
 pub struct MonorepoConfig {
     pub repo: RepoConfig,
+    #[serde(default)]
     pub projects: HashMap<String, ProjectConfig>,
+    #[serde(default)]
     pub packages: HashMap<String, PackageConfig>,
+    #[serde(default)]
     pub plugins: HashMap<String, PluginMeta>,
+    #[serde(default)]
+    pub recipes: HashMap<String, Recipe>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,14 +28,22 @@ pub struct RepoConfig {
 pub struct ProjectConfig {
     pub path: String,
     pub language: String,
+    #[serde(default)]
     pub permissions: Vec<(String, Vec<String>)>,
+    /// Plugin that created this entry, if any.
+    #[serde(default)]
+    pub plugin: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct PackageConfig {
     pub path: String,
     pub language: String,
+    #[serde(default)]
     pub permissions: Vec<(String, Vec<String>)>,
+    /// Plugin that created this entry, if any.
+    #[serde(default)]
+    pub plugin: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,6 +53,30 @@ pub enum PluginMeta {
     Detailed { version: String },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitKind {
+    Project,
+    Package,
+}
+
+impl UnitKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitKind::Project => "project",
+            UnitKind::Package => "package",
+        }
+    }
+}
+
+/// A project or package a plugin command is scoped to.
+#[derive(Debug, Clone)]
+pub struct MonorepoTarget {
+    pub name: String,
+    pub kind: UnitKind,
+    pub path: String,
+    pub language: String,
+}
+
 impl PluginMeta {
     pub fn version(&self) -> &str {
         match self {
@@ -46,6 +86,138 @@ impl PluginMeta {
     }
 }
 
+impl MonorepoConfig {
+    pub fn project_target(&self, name: &str) -> Option<MonorepoTarget> {
+        self.projects.get(name).map(|p| MonorepoTarget {
+            name: name.to_string(),
+            kind: UnitKind::Project,
+            path: p.path.clone(),
+            language: p.language.clone(),
+        })
+    }
+
+    pub fn package_target(&self, name: &str) -> Option<MonorepoTarget> {
+        self.packages.get(name).map(|p| MonorepoTarget {
+            name: name.to_string(),
+            kind: UnitKind::Package,
+            path: p.path.clone(),
+            language: p.language.clone(),
+        })
+    }
+
+    /// All projects and packages created by `plugin`, sorted by name.
+    pub fn targets_owned_by(&self, plugin: &str) -> Vec<MonorepoTarget> {
+        let mut targets: Vec<MonorepoTarget> = self
+            .projects
+            .iter()
+            .filter(|(_, p)| p.plugin.as_deref() == Some(plugin))
+            .filter_map(|(name, _)| self.project_target(name))
+            .chain(
+                self.packages
+                    .iter()
+                    .filter(|(_, p)| p.plugin.as_deref() == Some(plugin))
+                    .filter_map(|(name, _)| self.package_target(name)),
+            )
+            .collect();
+        targets.sort_by(|a, b| a.name.cmp(&b.name));
+
+        targets
+    }
+
+    /// Pairs of units where one path is nested inside the other, sorted by name.
+    pub fn overlapping_paths(&self) -> Vec<(String, String)> {
+        let mut units: Vec<(&str, Vec<&str>)> = self
+            .projects
+            .iter()
+            .map(|(name, p)| (name.as_str(), p.path.as_str()))
+            .chain(
+                self.packages
+                    .iter()
+                    .map(|(name, p)| (name.as_str(), p.path.as_str())),
+            )
+            .map(|(name, path)| {
+                let parts = path.split('/').filter(|p| !p.is_empty() && *p != ".");
+                (name, parts.collect())
+            })
+            .collect();
+        units.sort();
+
+        let mut overlaps = Vec::new();
+        for (i, (name, path)) in units.iter().enumerate() {
+            for (other, other_path) in &units[i + 1..] {
+                if path != other_path
+                    && (path.starts_with(other_path) || other_path.starts_with(path))
+                {
+                    overlaps.push((name.to_string(), other.to_string()));
+                }
+            }
+        }
+
+        overlaps
+    }
+
+    /// Semantic checks that deserialization alone does not cover.
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut paths: HashMap<&str, &str> = HashMap::new();
+
+        let units = self
+            .projects
+            .iter()
+            .map(|(name, p)| (name, p.path.as_str()))
+            .chain(
+                self.packages
+                    .iter()
+                    .map(|(name, p)| (name, p.path.as_str())),
+            );
+
+        for (name, path) in units {
+            if self.projects.contains_key(name) && self.packages.contains_key(name) {
+                return Err(format!("'{}' is declared both as project and package", name).into());
+            }
+
+            let path = path.trim_end_matches('/');
+            if path.is_empty() || path.starts_with('/') || path.split('/').any(|p| p == "..") {
+                return Err(format!(
+                    "'{}' must have a relative path inside the monorepo, got '{}'",
+                    name, path
+                )
+                .into());
+            }
+
+            if let Some(other) = paths.insert(path, name) {
+                return Err(format!("'{}' and '{}' share the path '{}'", other, name, path).into());
+            }
+        }
+
+        for (name, meta) in &self.plugins {
+            let version = meta.version();
+            if version != "*" && Version::parse(version).is_err() {
+                return Err(format!("Plugin '{}' has invalid version '{}'", name, version).into());
+            }
+        }
+
+        for (name, recipe) in &self.recipes {
+            recipe
+                .validate()
+                .map_err(|e| format!("Recipe '{}': {}", name, e))?;
+
+            if let Some(step) = recipe
+                .steps
+                .iter()
+                .find(|step| !self.plugins.contains_key(&step.plugin))
+            {
+                return Err(format!(
+                    "Recipe '{}' uses plugin '{}', which is not in [plugins]",
+                    name, step.plugin
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub fn get_monorepo_config(
     monorepo_root: &PathBuf,
 ) -> Result<MonorepoConfig, Box<dyn std::error::Error>> {
@@ -56,3 +228,22 @@ pub fn get_monorepo_config(
 
     Ok(monorepo_config)
 }
+
+/// Checks that `name` can go into the TOML, JSON and Starlark files of a
+/// template as is. npm rejects uppercase package names and Bazel module names
+/// must start with a lowercase letter, so only `^[a-z][a-z0-9_-]*$` is accepted.
+pub fn validate_repo_name(name: &str) -> Result<(), String> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'));
+
+    if !valid {
+        return Err(format!(
+            "Invalid repository name '{}': use lowercase letters, digits, '-' and '_', starting with a lowercase letter",
+            name
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::{Array, DocumentMut, Item, Table, TableLike, Value, value};
+
+use crate::monorepo::config::{MonorepoConfig, PackageConfig, ProjectConfig};
+
+/// Edits `monorepo.toml` in place, keeping comments, ordering and formatting
+/// of everything that isn't touched.
+pub struct MonorepoConfigEditor {
+    path: PathBuf,
+    doc: DocumentMut,
+}
+
+impl MonorepoConfigEditor {
+    pub fn open(monorepo_root: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = monorepo_root.join("monorepo.toml");
+        let content = fs::read_to_string(&path)?;
+
+        Self::parse(path, &content)
+    }
+
+    pub fn parse(path: PathBuf, content: &str) -> Result<Self, Box<dyn Error>> {
+        let doc = content
+            .parse::<DocumentMut>()
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+        Ok(Self { path, doc })
+    }
+
+    pub fn add_project(
+        &mut self,
+        name: &str,
+        project: &ProjectConfig,
+    ) -> Result<(), Box<dyn Error>> {
+        self.insert_unit(
+            "projects",
+            name,
+            unit_table(
+                &project.path,
+                &project.language,
+                &project.permissions,
+                project.plugin.as_deref(),
+            ),
+        )
+    }
+
+    pub fn remove_project(&mut self, name: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.section("projects")?.remove(name).is_some())
+    }
+
+    pub fn add_package(
+        &mut self,
+        name: &str,
+        package: &PackageConfig,
+    ) -> Result<(), Box<dyn Error>> {
+        self.insert_unit(
+            "packages",
+            name,
+            unit_table(
+                &package.path,
+                &package.language,
+                &package.permissions,
+                package.plugin.as_deref(),
+            ),
+        )
+    }
+
+    pub fn remove_package(&mut self, name: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.section("packages")?.remove(name).is_some())
+    }
+
+    /// Adds a plugin or bumps its version. Keeps the existing `name = "x"` or
+    /// `name = { version = "x" }` form of the entry.
+    pub fn set_plugin_version(&mut self, name: &str, version: &str) -> Result<(), Box<dyn Error>> {
+        let plugins = self.section("plugins")?;
+
+        match plugins.get_mut(name) {
+            Some(Item::Value(Value::InlineTable(table))) => match table.get_mut("version") {
+                Some(existing) => replace_value(existing, version),
+                None => {
+                    table.insert("version", version.into());
+                }
+            },
+            Some(Item::Table(table)) => match table.get_mut("version") {
+                Some(Item::Value(existing)) => replace_value(existing, version),
+                _ => {
+                    table.insert("version", value(version));
+                }
+            },
+            Some(Item::Value(existing)) => replace_value(existing, version),
+            _ => {
+                plugins.insert(name, value(version));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_plugin(&mut self, name: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.section("plugins")?.remove(name).is_some())
+    }
+
+    /// Deserializes the edited document and runs the semantic checks on it.
+    pub fn validate(&self) -> Result<MonorepoConfig, Box<dyn Error>> {
+        let config: MonorepoConfig = toml::from_str(&self.doc.to_string())
+            .map_err(|e| format!("Edited monorepo.toml is invalid: {}", e))?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Validates the document and writes it back through a temporary file.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        self.validate()?;
+
+        let tmp_path = self.path.with_extension("toml.tmp");
+        fs::write(&tmp_path, self.doc.to_string())?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+
+    pub fn contents(&self) -> String {
+        self.doc.to_string()
+    }
+
+    fn section(&mut self, name: &str) -> Result<&mut dyn TableLike, Box<dyn Error>> {
+        if !self.doc.contains_key(name) {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            self.doc.insert(name, Item::Table(table));
+        }
+
+        self.doc[name]
+            .as_table_like_mut()
+            .ok_or_else(|| format!("'{}' in monorepo.toml is not a table", name).into())
+    }
+
+    fn insert_unit(
+        &mut self,
+        section: &str,
+        name: &str,
+        table: Table,
+    ) -> Result<(), Box<dyn Error>> {
+        for other in ["projects", "packages"] {
+            if self.doc.get(other).and_then(|s| s.get(name)).is_some() {
+                return Err(format!("'{}' is already registered in [{}]", name, other).into());
+            }
+        }
+
+        self.section(section)?.insert(name, Item::Table(table));
+
+        Ok(())
+    }
+}
+
+fn unit_table(
+    path: &str,
+    language: &str,
+    permissions: &[(String, Vec<String>)],
+    plugin: Option<&str>,
+) -> Table {
+    let mut table = Table::new();
+    table.insert("path", value(path));
+    table.insert("language", value(language));
+
+    if let Some(plugin) = plugin {
+        table.insert("plugin", value(plugin));
+    }
+
+    if !permissions.is_empty() {
+        let mut entries = Array::new();
+        for (scope, targets) in permissions {
+            let mut pair = Array::new();
+            pair.push(scope.as_str());
+            pair.push(targets.iter().map(String::as_str).collect::<Array>());
+            entries.push(pair);
+        }
+        table.insert("permissions", value(entries));
+    }
+
+    table
+}
+
+fn replace_value(existing: &mut Value, version: &str) {
+    let decor = existing.decor().clone();
+    *existing = Value::from(version);
+    *existing.decor_mut() = decor;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONOREPO_TOML: &str = r#"# Monorepo of the demo team
+[repo]
+name = "demo" # shown in apix status
+version = "0.1.0"
+template = "bazel"
+
+# Services
+[projects.api]
+path = "apps/api"
+language = "rust"
+
+[plugins]
+# Pinned until the next release
+rust = "0.1.0" # keep in sync with CI
+node = { version = "0.2.0" } # inline
+"#;
+
+    fn editor() -> MonorepoConfigEditor {
+        MonorepoConfigEditor::parse(PathBuf::from("monorepo.toml"), MONOREPO_TOML).unwrap()
+    }
+
+    #[test]
+    fn add_project_keeps_comments() {
+        let mut editor = editor();
+        let project = ProjectConfig {
+            path: "apps/web".to_string(),
+            language: "typescript".to_string(),
+            permissions: Vec::new(),
+            plugin: Some("node".to_string()),
+        };
+        editor.add_project("web", &project).unwrap();
+
+        let contents = editor.contents();
+        assert!(contents.starts_with("# Monorepo of the demo team\n"));
+        assert!(contents.contains("name = \"demo\" # shown in apix status\n"));
+        assert!(contents.contains("# Services\n[projects.api]\n"));
+        assert!(contents.contains("# Pinned until the next release\n"));
+        assert!(contents.contains(
+            "[projects.web]\npath = \"apps/web\"\nlanguage = \"typescript\"\nplugin = \"node\"\n"
+        ));
+        editor.validate().unwrap();
+    }
+
+    #[test]
+    fn set_plugin_version_keeps_comments_and_form() {
+        let mut editor = editor();
+        editor.set_plugin_version("rust", "0.2.0").unwrap();
+        editor.set_plugin_version("node", "0.3.0").unwrap();
+        editor.set_plugin_version("go", "1.0.0").unwrap();
+
+        let contents = editor.contents();
+        assert!(contents.contains("# Pinned until the next release\n"));
+        assert!(contents.contains("rust = \"0.2.0\" # keep in sync with CI\n"));
+        assert!(contents.contains("node = { version = \"0.3.0\" } # inline\n"));
+        assert!(contents.contains("go = \"1.0.0\"\n"));
+        assert_eq!(
+            contents.replace("0.2.0", "0.1.0").replace("0.3.0", "0.2.0"),
+            format!("{}go = \"1.0.0\"\n", MONOREPO_TOML)
+        );
+    }
+}
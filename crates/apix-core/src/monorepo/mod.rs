@@ -1 +1,4 @@
+pub mod adopt;
 pub mod config;
+pub mod editor;
+pub mod recipe;
@@ -0,0 +1,124 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use crate::{monorepo::config::MonorepoConfig, template::render::render_str};
+
+/// Actions a recipe step can run; both produce a plan without needing
+/// anything from the previous run of the plugin.
+pub const RECIPE_ACTIONS: &[&str] = &["create", "extend"];
+
+/// Plugin actions run one after the other and applied as a single plan, from
+/// a recipe file or `[recipes.<name>]` in monorepo.toml.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Recipe {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<RecipeStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecipeStep {
+    pub plugin: String,
+    /// `create` or `extend`.
+    pub action: String,
+    /// Project name for `create`, arguments for `extend`. May use `{{var}}`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub project: Option<String>,
+    pub package: Option<String>,
+}
+
+impl Recipe {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let recipe: Recipe =
+            toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        recipe.validate()?;
+
+        Ok(recipe)
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.steps.is_empty() {
+            return Err("Recipe has no steps".into());
+        }
+
+        for (i, step) in self.steps.iter().enumerate() {
+            if !RECIPE_ACTIONS.contains(&step.action.as_str()) {
+                return Err(format!(
+                    "Step {} runs '{}', recipes support {}",
+                    i + 1,
+                    step.action,
+                    RECIPE_ACTIONS.join(" and ")
+                )
+                .into());
+            }
+            if step.project.is_some() && step.package.is_some() {
+                return Err(format!("Step {} targets both a project and a package", i + 1).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Steps with `{{var}}` placeholders replaced by `vars`. Fails on
+    /// placeholders without a value.
+    pub fn render(
+        &self,
+        vars: &HashMap<String, String>,
+    ) -> Result<Vec<RecipeStep>, Box<dyn Error>> {
+        let placeholder = Regex::new(r"\{\{\s*([A-Za-z0-9_-]+)\s*\}\}").unwrap();
+        let render = |value: &str| -> Result<String, Box<dyn Error>> {
+            let rendered = render_str(value, vars);
+            match placeholder.captures(&rendered) {
+                Some(c) => Err(format!(
+                    "Recipe variable '{}' is not set, pass --set {}=<value>",
+                    &c[1], &c[1]
+                )
+                .into()),
+                None => Ok(rendered),
+            }
+        };
+
+        self.steps
+            .iter()
+            .map(|step| {
+                Ok(RecipeStep {
+                    plugin: step.plugin.clone(),
+                    action: step.action.clone(),
+                    args: step
+                        .args
+                        .iter()
+                        .map(|arg| render(arg))
+                        .collect::<Result<_, _>>()?,
+                    project: step.project.as_deref().map(render).transpose()?,
+                    package: step.package.as_deref().map(render).transpose()?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The recipe `name` from monorepo.toml, or else the recipe file at `name`.
+pub fn find_recipe(
+    monorepo_root: &Path,
+    config: &MonorepoConfig,
+    name: &str,
+) -> Result<Recipe, Box<dyn Error>> {
+    if let Some(recipe) = config.recipes.get(name) {
+        return Ok(recipe.clone());
+    }
+
+    let path = monorepo_root.join(name);
+    if path.is_file() {
+        return Recipe::load(&path);
+    }
+
+    Err(format!(
+        "'{}' is neither a recipe in monorepo.toml nor a recipe file",
+        name
+    )
+    .into())
+}
@@ -0,0 +1,305 @@
+use clap::{Arg, ArgAction, ArgMatches, Command, builder::PossibleValuesParser, value_parser};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Argument names apix uses for the flags it adds to every plugin command,
+/// and `force` of `extend`.
+pub const RESERVED_ARGS: &[&str] = &[
+    "project",
+    "package",
+    "yes",
+    "accept_all",
+    "allow-dirty",
+    "allow_dirty",
+    "force",
+    "help",
+];
+/// Short flags apix adds to every plugin command, `-y` and `-h`.
+pub const RESERVED_SHORTS: &[char] = &['y', 'h'];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgKind {
+    #[default]
+    String,
+    Bool,
+    Int,
+    /// One of `values`.
+    Enum,
+}
+
+impl ArgKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArgKind::String => "string",
+            ArgKind::Bool => "bool",
+            ArgKind::Int => "int",
+            ArgKind::Enum => "enum",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ArgValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+/// Parsed arguments by name, handed to the plugin as a table.
+pub type ArgValues = BTreeMap<String, ArgValue>;
+
+/// A positional argument or `--<name>` flag of a plugin command.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArgSpec {
+    pub name: String,
+    #[serde(default)]
+    pub help: String,
+    #[serde(default, rename = "type")]
+    pub kind: ArgKind,
+    /// Allowed values of an `enum` argument.
+    #[serde(default)]
+    pub values: Vec<String>,
+    /// Taken by position instead of `--<name>`.
+    #[serde(default)]
+    pub positional: bool,
+    pub short: Option<char>,
+    #[serde(default)]
+    pub required: bool,
+    pub default: Option<ArgValue>,
+}
+
+impl ArgSpec {
+    pub fn validate(&self) -> Result<(), String> {
+        let fail = |message: &str| Err(format!("argument '{}' {}", self.name, message));
+
+        if RESERVED_ARGS.contains(&self.name.as_str()) {
+            return fail("uses a name reserved by apix");
+        }
+        match (self.kind, self.values.is_empty()) {
+            (ArgKind::Enum, true) => return fail("is an enum without values"),
+            (ArgKind::Enum, false) => {}
+            (_, false) => return fail("has values but is not an enum"),
+            _ => {}
+        }
+        if self.kind == ArgKind::Bool && (self.positional || self.required) {
+            return fail("is a bool, which can only be an optional flag");
+        }
+        // A flag can only turn a bool on, so a `true` default could never be unset.
+        if self.kind == ArgKind::Bool && self.default == Some(ArgValue::Bool(true)) {
+            return fail("is a bool flag and cannot default to true");
+        }
+        if self.positional && self.short.is_some() {
+            return fail("is positional and cannot have a short flag");
+        }
+        if let Some(short) = self.short
+            && !short.is_ascii_alphanumeric()
+        {
+            return fail(&format!(
+                "uses '-{}' as short flag, which must be a letter or digit",
+                short
+            ));
+        }
+        if let Some(short) = self.short
+            && RESERVED_SHORTS.contains(&short)
+        {
+            return fail(&format!(
+                "uses the short flag '-{}' reserved by apix",
+                short
+            ));
+        }
+        if self.required && self.default.is_some() {
+            return fail("is required and cannot have a default");
+        }
+
+        if let Some(default) = &self.default {
+            let matches = match (self.kind, default) {
+                (ArgKind::String, ArgValue::String(_))
+                | (ArgKind::Bool, ArgValue::Bool(_))
+                | (ArgKind::Int, ArgValue::Int(_)) => true,
+                (ArgKind::Enum, ArgValue::String(value)) => self.values.contains(value),
+                _ => false,
+            };
+            if !matches {
+                return fail(&format!(
+                    "has a default that is not a valid {}",
+                    self.kind.as_str()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// How the argument is written on the command line, e.g. `<name>` or `-n, --name <name>`.
+    pub fn usage(&self) -> String {
+        let value = match self.kind {
+            ArgKind::Bool => String::new(),
+            _ => format!(" <{}>", self.name),
+        };
+
+        match (self.positional, self.short) {
+            (true, _) => value.trim_start().to_string(),
+            (false, Some(short)) => format!("-{}, --{}{}", short, self.name, value),
+            (false, None) => format!("--{}{}", self.name, value),
+        }
+    }
+
+    /// Help text with the default appended.
+    pub fn description(&self) -> String {
+        match &self.default {
+            Some(default) => format!("{} [default: {}]", self.help, default)
+                .trim()
+                .to_string(),
+            None => self.help.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for ArgValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgValue::Bool(value) => write!(f, "{}", value),
+            ArgValue::Int(value) => write!(f, "{}", value),
+            ArgValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Checks every argument, that names and short flags are unique and that no
+/// required positional follows an optional one.
+pub fn validate_args(args: &[ArgSpec]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    let mut shorts = HashSet::new();
+    let mut optional_positional = None;
+
+    for arg in args {
+        arg.validate()?;
+        if arg.positional {
+            match optional_positional {
+                Some(optional) if arg.required => {
+                    return Err(format!(
+                        "required argument '{}' follows the optional positional '{}'",
+                        arg.name, optional
+                    ));
+                }
+                None if !arg.required => optional_positional = Some(&arg.name),
+                _ => {}
+            }
+        }
+        if !names.insert(arg.name.as_str()) {
+            return Err(format!("argument '{}' is declared twice", arg.name));
+        }
+        if let Some(short) = arg.short
+            && !shorts.insert(short)
+        {
+            return Err(format!("short flag '-{}' is used twice", short));
+        }
+    }
+
+    Ok(())
+}
+
+/// `[extend]` of plugin.toml.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ExtendSpec {
+    /// Arguments of `extend`; when set the plugin gets them as a table instead of strings.
+    #[serde(default)]
+    pub args: Vec<ArgSpec>,
+}
+
+/// A subcommand declared under `[commands.<name>]` in plugin.toml.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommandSpec {
+    #[serde(default)]
+    pub description: String,
+    /// Global function to call, the command name with `-` replaced by `_` by default.
+    pub function: Option<String>,
+    #[serde(default)]
+    pub args: Vec<ArgSpec>,
+}
+
+impl CommandSpec {
+    pub fn function_name(&self, name: &str) -> String {
+        self.function
+            .clone()
+            .unwrap_or_else(|| name.replace('-', "_"))
+    }
+}
+
+/// Adds the arguments a plugin declared to `command`.
+pub fn add_declared_args(mut command: Command, args: &[ArgSpec]) -> Command {
+    for spec in args {
+        let mut arg = Arg::new(spec.name.clone())
+            .help(spec.description())
+            .required(spec.required);
+
+        if !spec.positional {
+            arg = arg.long(spec.name.clone());
+            if let Some(short) = spec.short {
+                arg = arg.short(short);
+            }
+        }
+
+        arg = match spec.kind {
+            ArgKind::String => arg,
+            ArgKind::Bool => arg.action(ArgAction::SetTrue),
+            ArgKind::Int => arg.value_parser(value_parser!(i64)),
+            ArgKind::Enum => arg.value_parser(PossibleValuesParser::new(spec.values.clone())),
+        };
+
+        command = command.arg(arg);
+    }
+
+    command
+}
+
+/// Values of the declared arguments, with defaults for the ones not given.
+/// Bool flags are always set.
+pub fn declared_values(matches: &ArgMatches, args: &[ArgSpec]) -> ArgValues {
+    let mut values = ArgValues::new();
+
+    for spec in args {
+        let value = match spec.kind {
+            ArgKind::Bool if matches.get_flag(&spec.name) => Some(ArgValue::Bool(true)),
+            ArgKind::Bool => Some(spec.default.clone().unwrap_or(ArgValue::Bool(false))),
+            ArgKind::Int => matches
+                .get_one::<i64>(&spec.name)
+                .map(|value| ArgValue::Int(*value)),
+            ArgKind::String | ArgKind::Enum => matches
+                .get_one::<String>(&spec.name)
+                .map(|value| ArgValue::String(value.clone())),
+        };
+
+        if let Some(value) = value.or_else(|| spec.default.clone()) {
+            values.insert(spec.name.clone(), value);
+        }
+    }
+
+    values
+}
+
+/// Parsed values as `name=value` strings, in name order, for the event history.
+pub fn event_args(values: &ArgValues) -> Vec<String> {
+    values
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect()
+}
+
+/// Parses `args` against `specs`, which must pass [`validate_args`]. `-h` and
+/// `--help` are not handled, callers answer them with the plugin's info.
+pub fn parse_args(
+    bin_name: String,
+    specs: &[ArgSpec],
+    args: &[String],
+) -> Result<ArgValues, clap::Error> {
+    let command = Command::new(bin_name.clone())
+        .bin_name(bin_name)
+        .no_binary_name(true)
+        .disable_help_flag(true);
+    let matches = add_declared_args(command, specs).try_get_matches_from(args)?;
+
+    Ok(declared_values(&matches, specs))
+}
@@ -0,0 +1,726 @@
+use mlua::Lua;
+use regex::Regex;
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::plugin::{
+    config::read_plugin_config,
+    loader::entry_path,
+    tools::tool_exists,
+    types::{ApiFunction, DEFINITIONS_FILE, ctx_api, generate_definitions},
+    validate::Diagnostic,
+};
+
+/// External analyzer used when it is on PATH.
+const LUAU_LSP: &str = "luau-lsp";
+
+/// Which checks produced a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Analyzer {
+    /// `luau-lsp analyze` against the generated ctx definitions.
+    LuauLsp,
+    /// Syntax check plus apix's own lint of `ctx` calls.
+    Builtin,
+}
+
+impl Analyzer {
+    /// What the checks covered, shown next to their result.
+    pub fn scope(&self) -> &'static str {
+        match self {
+            Analyzer::LuauLsp => "type checked with luau-lsp",
+            Analyzer::Builtin => {
+                "syntax and ctx lint only, no type checking: install luau-lsp for full checks"
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CheckReport {
+    pub entry: PathBuf,
+    pub analyzer: Analyzer,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Whether a source file opts into the load-time check with `--!strict`.
+pub fn is_strict(source: &str) -> bool {
+    source
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| line == "--!strict")
+}
+
+/// Type-checks the entry file of the plugin in `plugin_dir` without running it.
+pub fn check_plugin(plugin_dir: &Path) -> Result<CheckReport, Box<dyn Error>> {
+    let config = read_plugin_config(plugin_dir)?;
+    let entry = entry_path(plugin_dir, &config.name)?;
+    let source = fs::read_to_string(&entry)?;
+    let (analyzer, diagnostics) = check_file(&entry, &source)?;
+
+    Ok(CheckReport {
+        entry,
+        analyzer,
+        diagnostics,
+    })
+}
+
+/// Type-checks one source file, with luau-lsp when it is on PATH.
+pub fn check_file(
+    path: &Path,
+    source: &str,
+) -> Result<(Analyzer, Vec<Diagnostic>), Box<dyn Error>> {
+    if let Some(diagnostic) = syntax_error(path, source) {
+        return Ok((Analyzer::Builtin, vec![diagnostic]));
+    }
+
+    Ok(if tool_exists(LUAU_LSP) {
+        (Analyzer::LuauLsp, run_luau_lsp(path)?)
+    } else {
+        (Analyzer::Builtin, lint_ctx_calls(path, source))
+    })
+}
+
+fn syntax_error(entry: &Path, source: &str) -> Option<Diagnostic> {
+    let error = Lua::new()
+        .load(source)
+        .set_name(format!("@{}", entry.display()))
+        .into_function()
+        .err()?;
+
+    let message = match &error {
+        mlua::Error::SyntaxError { message, .. } => message.clone(),
+        other => other.to_string(),
+    };
+
+    let position = Regex::new(r":(\d+): (.*)$").unwrap();
+    Some(
+        match position.captures(message.lines().next().unwrap_or_default()) {
+            Some(c) => Diagnostic::error(c[2].to_string()).at(entry, c[1].parse().unwrap_or(1), 1),
+            None => Diagnostic::error(message),
+        },
+    )
+}
+
+fn run_luau_lsp(entry: &Path) -> Result<Vec<Diagnostic>, Box<dyn Error>> {
+    let definitions_dir = tempfile::tempdir()?;
+    let definitions = definitions_dir.path().join(DEFINITIONS_FILE);
+    fs::write(&definitions, generate_definitions()?)?;
+
+    let output = Command::new(LUAU_LSP)
+        .arg("analyze")
+        .arg(format!("--definitions={}", definitions.display()))
+        .arg(entry)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", LUAU_LSP, e))?;
+
+    // `file(line,col): Kind: message`
+    let line_re = Regex::new(r"^(.+)\((\d+),(\d+)(?:-\d+)?\): (\w+): (.*)$").unwrap();
+    let mut diagnostics = Vec::new();
+
+    for line in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+    {
+        let Some(c) = line_re.captures(line.trim()) else {
+            continue;
+        };
+        let message = format!("{}: {}", &c[4], &c[5]);
+        let diagnostic = if c[4].ends_with("Error") {
+            Diagnostic::error(message)
+        } else {
+            Diagnostic::warning(message)
+        };
+        diagnostics.push(diagnostic.at(
+            entry,
+            c[2].parse().unwrap_or(1),
+            c[3].parse().unwrap_or(1),
+        ));
+    }
+
+    if !output.status.success() && diagnostics.is_empty() {
+        return Err(format!(
+            "{} analyze failed: {}",
+            LUAU_LSP,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(diagnostics)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Number,
+    Str,
+    Punct(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.kind, TokenKind::Punct(p) if p == punct)
+    }
+
+    fn ident(&self) -> Option<&str> {
+        match &self.kind {
+            TokenKind::Ident(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+const PUNCTS: &[&str] = &[
+    "...", "::", "..", "==", "~=", "<=", ">=", "->", "+=", "-=", "(", ")", "{", "}", "[", "]", ",",
+    ";", ":", ".", "=", "+", "-", "*", "/", "%", "^", "#", "<", ">", "?", "|", "&",
+];
+
+/// Splits Luau source into the tokens the lint needs, skipping comments.
+fn tokenize(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut line_start) = (0, 1, 0);
+
+    let long_bracket = |at: usize| -> Option<usize> {
+        // `[[` or `[==[`, returns the number of `=`.
+        if chars.get(at) != Some(&'[') {
+            return None;
+        }
+        let level = chars[at + 1..].iter().take_while(|c| **c == '=').count();
+        (chars.get(at + 1 + level) == Some(&'[')).then_some(level)
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i - line_start + 1;
+        let start_line = line;
+
+        let mut skip_to = |end: usize, i: &mut usize| {
+            for (offset, ch) in chars[*i..end.min(chars.len())].iter().enumerate() {
+                if *ch == '\n' {
+                    line += 1;
+                    line_start = *i + offset + 1;
+                }
+            }
+            *i = end.min(chars.len());
+        };
+
+        let find_long_end = |from: usize, level: usize| -> usize {
+            let close: String = format!("]{}]", "=".repeat(level));
+            let rest: String = chars[from..].iter().collect();
+            match rest.find(&close) {
+                Some(pos) => from + rest[..pos].chars().count() + close.len(),
+                None => chars.len(),
+            }
+        };
+
+        if c.is_whitespace() {
+            skip_to(i + 1, &mut i);
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            let end = match long_bracket(i + 2) {
+                Some(level) => find_long_end(i + 4 + level, level),
+                None => (i..chars.len())
+                    .find(|j| chars[*j] == '\n')
+                    .unwrap_or(chars.len()),
+            };
+            skip_to(end, &mut i);
+        } else if let Some(level) = long_bracket(i) {
+            let end = find_long_end(i + 2 + level, level);
+            skip_to(end, &mut i);
+            tokens.push(token(TokenKind::Str, start_line, column));
+        } else if c == '"' || c == '\'' || c == '`' {
+            let mut j = i + 1;
+            while j < chars.len() && chars[j] != c && chars[j] != '\n' {
+                j += if chars[j] == '\\' { 2 } else { 1 };
+            }
+            skip_to(j + 1, &mut i);
+            tokens.push(token(TokenKind::Str, start_line, column));
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()))
+        {
+            let mut j = i + 1;
+            while j < chars.len()
+                && (chars[j].is_ascii_alphanumeric()
+                    || chars[j] == '.'
+                    || chars[j] == '_'
+                    || (matches!(chars[j], '+' | '-') && matches!(chars[j - 1], 'e' | 'E')))
+            {
+                j += 1;
+            }
+            skip_to(j, &mut i);
+            tokens.push(token(TokenKind::Number, start_line, column));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut j = i + 1;
+            while j < chars.len() && (chars[j].is_alphanumeric() || chars[j] == '_') {
+                j += 1;
+            }
+            let name: String = chars[i..j].iter().collect();
+            skip_to(j, &mut i);
+            tokens.push(token(TokenKind::Ident(name), start_line, column));
+        } else {
+            let punct = PUNCTS
+                .iter()
+                .find(|p| chars[i..].iter().take(p.len()).copied().eq(p.chars()))
+                .copied();
+            match punct {
+                Some(p) => {
+                    skip_to(i + p.len(), &mut i);
+                    tokens.push(token(TokenKind::Punct(p), start_line, column));
+                }
+                None => skip_to(i + 1, &mut i),
+            }
+        }
+    }
+
+    tokens
+}
+
+fn token(kind: TokenKind, line: usize, column: usize) -> Token {
+    Token { kind, line, column }
+}
+
+/// A call argument as far as the lint can tell from its tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgKind {
+    String,
+    Number,
+    Boolean,
+    Nil,
+    Table,
+    Function,
+    /// Any other expression.
+    Unknown,
+    /// A trailing call or `...`, which may expand to several values.
+    Multiple,
+}
+
+impl ArgKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ArgKind::String => "string",
+            ArgKind::Number => "number",
+            ArgKind::Boolean => "boolean",
+            ArgKind::Nil => "nil",
+            ArgKind::Table => "table",
+            ArgKind::Function => "function",
+            ArgKind::Unknown | ArgKind::Multiple => "any",
+        }
+    }
+}
+
+struct Param {
+    name: String,
+    ty: String,
+    optional: bool,
+}
+
+/// Parameters of a declared signature like `(path: string, args: { string }?) -> ()`.
+fn parse_params(signature: &str) -> Vec<Param> {
+    let inner = signature
+        .split_once(" -> ")
+        .map_or(signature, |(params, _)| params)
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')');
+
+    split_top_level(inner)
+        .into_iter()
+        .filter_map(|param| {
+            let (name, ty) = param.split_once(':')?;
+            let ty = ty.trim().to_string();
+            Some(Param {
+                name: name.trim().to_string(),
+                optional: ty.ends_with('?'),
+                ty,
+            })
+        })
+        .collect()
+}
+
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        parts.push(s[start..].trim());
+    }
+    parts
+}
+
+/// The kind of value a declared type accepts, `None` if the lint cannot tell.
+fn expected_kind(ty: &str) -> Option<ArgKind> {
+    let ty = ty.trim_end_matches('?').trim();
+    match ty {
+        "string" => Some(ArgKind::String),
+        "number" => Some(ArgKind::Number),
+        "boolean" => Some(ArgKind::Boolean),
+        _ if ty.starts_with('{') => Some(ArgKind::Table),
+        // Type aliases in the generated definitions are all table shapes.
+        _ if ty.chars().next().is_some_and(char::is_uppercase) && !ty.contains('|') => {
+            Some(ArgKind::Table)
+        }
+        _ => None,
+    }
+}
+
+/// Checks `ctx` usage against the declared API: colon calls, unknown members,
+/// argument counts and literal argument types.
+fn lint_ctx_calls(entry: &Path, source: &str) -> Vec<Diagnostic> {
+    let tokens = tokenize(source);
+    let api: Vec<&ApiFunction> = ctx_api().collect();
+    let mut diagnostics = Vec::new();
+
+    for i in 0..tokens.len() {
+        if tokens[i].ident() != Some("ctx")
+            || (i > 0 && (tokens[i - 1].is_punct(".") || tokens[i - 1].is_punct(":")))
+        {
+            continue;
+        }
+
+        let (Some(separator), Some(member)) = (tokens.get(i + 1), tokens.get(i + 2)) else {
+            continue;
+        };
+        let Some(name) = member.ident() else {
+            continue;
+        };
+        let function = api.iter().find(|f| f.name == name);
+        let at = |d: Diagnostic| d.at(entry, member.line, member.column);
+
+        if separator.is_punct(":") {
+            diagnostics.push(at(Diagnostic::error(match function {
+                Some(_) => format!(
+                    "'ctx:{}' passes ctx as the first argument, call it as 'ctx.{}'",
+                    name, name
+                ),
+                None => format!("ctx has no function '{}'", name),
+            })));
+            continue;
+        }
+
+        if !separator.is_punct(".") || name == "monorepo" {
+            continue;
+        }
+
+        let Some(function) = function else {
+            let called = tokens.get(i + 3).is_some_and(|t| t.is_punct("("));
+            let message = format!("ctx has no member '{}'", name);
+            diagnostics.push(at(if called {
+                Diagnostic::error(message)
+            } else {
+                Diagnostic::warning(message)
+            }));
+            continue;
+        };
+
+        if let Some(args) = call_args(&tokens, i + 3) {
+            check_call(function, &args, &mut |d| diagnostics.push(at(d)));
+        }
+    }
+
+    diagnostics
+}
+
+/// Argument kinds of the call starting at `start`, supporting the `f "s"` and
+/// `f { ... }` call forms. `None` if the function is not called there.
+fn call_args(tokens: &[Token], start: usize) -> Option<Vec<ArgKind>> {
+    let open = tokens.get(start)?;
+    match &open.kind {
+        TokenKind::Str => return Some(vec![ArgKind::String]),
+        TokenKind::Punct("{") => return Some(vec![ArgKind::Table]),
+        TokenKind::Punct("(") => {}
+        _ => return None,
+    }
+
+    let mut args = Vec::new();
+    let mut current: Vec<&Token> = Vec::new();
+    let mut depth = 0;
+
+    for token in &tokens[start + 1..] {
+        match &token.kind {
+            TokenKind::Punct("(" | "{" | "[") => depth += 1,
+            TokenKind::Punct(")") if depth == 0 => {
+                if !current.is_empty() {
+                    args.push(classify(&current, true));
+                }
+                return Some(args);
+            }
+            TokenKind::Punct(")" | "}" | "]") => depth -= 1,
+            TokenKind::Punct(",") if depth == 0 => {
+                args.push(classify(&current, false));
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(token);
+    }
+
+    None
+}
+
+fn classify(tokens: &[&Token], last: bool) -> ArgKind {
+    let Some(first) = tokens.first().map(|t| &t.kind) else {
+        return ArgKind::Unknown;
+    };
+    let ends_with_call =
+        tokens.len() > 1 && tokens[tokens.len() - 1].is_punct(")") && !parenthesized(tokens);
+
+    if last && (ends_with_call || tokens[0].is_punct("...")) {
+        return ArgKind::Multiple;
+    }
+
+    if tokens.len() == 1 {
+        return match first {
+            TokenKind::Str => ArgKind::String,
+            TokenKind::Number => ArgKind::Number,
+            TokenKind::Ident(name) if name == "true" || name == "false" => ArgKind::Boolean,
+            TokenKind::Ident(name) if name == "nil" => ArgKind::Nil,
+            _ => ArgKind::Unknown,
+        };
+    }
+
+    // A single table constructor or function literal spanning the whole argument.
+    match first {
+        TokenKind::Punct("{") if tokens[tokens.len() - 1].is_punct("}") => {
+            let mut depth = 0;
+            let closes_at_end = tokens.iter().enumerate().all(|(i, t)| {
+                match &t.kind {
+                    TokenKind::Punct("{") => depth += 1,
+                    TokenKind::Punct("}") => depth -= 1,
+                    _ => {}
+                }
+                depth > 0 || i == tokens.len() - 1
+            });
+            if closes_at_end {
+                ArgKind::Table
+            } else {
+                ArgKind::Unknown
+            }
+        }
+        TokenKind::Ident(name) if name == "function" => ArgKind::Function,
+        // Concatenation at the top level always yields a string.
+        _ if concatenates(tokens) => ArgKind::String,
+        _ => ArgKind::Unknown,
+    }
+}
+
+/// Whether the whole argument is one `( ... )`, which yields a single value.
+fn parenthesized(tokens: &[&Token]) -> bool {
+    let mut depth = 0;
+    tokens.first().is_some_and(|t| t.is_punct("("))
+        && tokens.iter().enumerate().all(|(i, t)| {
+            match &t.kind {
+                TokenKind::Punct("(" | "{" | "[") => depth += 1,
+                TokenKind::Punct(")" | "}" | "]") => depth -= 1,
+                _ => {}
+            }
+            depth > 0 || i == tokens.len() - 1
+        })
+}
+
+fn concatenates(tokens: &[&Token]) -> bool {
+    let mut depth = 0;
+    tokens.iter().any(|t| {
+        match &t.kind {
+            TokenKind::Punct("(" | "{" | "[") => depth += 1,
+            TokenKind::Punct(")" | "}" | "]") => depth -= 1,
+            _ => {}
+        }
+        depth == 0 && t.is_punct("..")
+    })
+}
+
+fn check_call(function: &ApiFunction, args: &[ArgKind], report: &mut dyn FnMut(Diagnostic)) {
+    let params = parse_params(function.signature);
+    let required = params.iter().filter(|p| !p.optional).count();
+    let expands = args.last() == Some(&ArgKind::Multiple);
+
+    if args.len() > params.len() {
+        report(Diagnostic::error(format!(
+            "ctx.{} takes {} argument(s), got {}",
+            function.name,
+            params.len(),
+            args.len()
+        )));
+    } else if args.len() < required && !expands {
+        report(Diagnostic::error(format!(
+            "ctx.{} expects {} argument(s), got {}",
+            function.name,
+            required,
+            args.len()
+        )));
+    }
+
+    for (index, (param, arg)) in params.iter().zip(args).enumerate() {
+        let Some(expected) = expected_kind(&param.ty) else {
+            continue;
+        };
+        let mismatch = match arg {
+            ArgKind::Unknown | ArgKind::Multiple => false,
+            ArgKind::Nil => !param.optional,
+            other => *other != expected,
+        };
+        if mismatch {
+            report(Diagnostic::error(format!(
+                "argument #{} '{}' of ctx.{} must be {}, got {}",
+                index + 1,
+                param.name,
+                function.name,
+                param.ty,
+                arg.name()
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source).into_iter().map(|t| t.kind).collect()
+    }
+
+    fn ident(name: &str) -> TokenKind {
+        TokenKind::Ident(name.to_string())
+    }
+
+    fn args_of(source: &str) -> Option<Vec<ArgKind>> {
+        let tokens = tokenize(source);
+        // The call starts after `f`.
+        call_args(&tokens, 1)
+    }
+
+    fn lint(source: &str) -> Vec<String> {
+        lint_ctx_calls(Path::new("main.luau"), source)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn tokenize_skips_comments_and_tracks_positions() {
+        let source = "-- ctx.log\nlocal s = [==[ctx.\n]] ]==] --[[ ctx:x ]] .. 'a\\'b'\nx...y";
+        assert_eq!(
+            kinds(source),
+            vec![
+                ident("local"),
+                ident("s"),
+                TokenKind::Punct("="),
+                TokenKind::Str,
+                TokenKind::Punct(".."),
+                TokenKind::Str,
+                ident("x"),
+                TokenKind::Punct("..."),
+                ident("y"),
+            ]
+        );
+
+        let tokens = tokenize(source);
+        assert_eq!((tokens[0].line, tokens[0].column), (2, 1));
+        assert_eq!((tokens[4].line, tokens[4].column), (3, 23));
+        assert_eq!((tokens[6].line, tokens[6].column), (4, 1));
+    }
+
+    #[test]
+    fn tokenize_numbers() {
+        assert_eq!(
+            kinds("1e-3 0x1F .5 x..3"),
+            vec![
+                TokenKind::Number,
+                TokenKind::Number,
+                TokenKind::Number,
+                ident("x"),
+                TokenKind::Punct(".."),
+                TokenKind::Number,
+            ]
+        );
+    }
+
+    #[test]
+    fn call_args_classifies_each_argument() {
+        use ArgKind::*;
+
+        assert_eq!(
+            args_of(r#"f("a", 1, true, nil, { x = { 1 } }, function() end, y)"#),
+            Some(vec![String, Number, Boolean, Nil, Table, Function, Unknown])
+        );
+        assert_eq!(
+            args_of(r#"f("a" .. x, (1 .. 2))"#),
+            Some(vec![String, Unknown])
+        );
+        assert_eq!(args_of("f({} , {}.x)"), Some(vec![Table, Unknown]));
+        assert_eq!(args_of("f()"), Some(vec![]));
+        assert_eq!(args_of(r#"f "a""#), Some(vec![String]));
+        assert_eq!(args_of("f { 1 }"), Some(vec![Table]));
+        assert_eq!(args_of("f.x"), None);
+    }
+
+    #[test]
+    fn call_args_marks_trailing_expansions() {
+        use ArgKind::*;
+
+        assert_eq!(args_of("f(g(), h())"), Some(vec![Unknown, Multiple]));
+        assert_eq!(args_of("f(1, (g)())"), Some(vec![Number, Multiple]));
+        assert_eq!(args_of("f(1, (g()))"), Some(vec![Number, Unknown]));
+        assert_eq!(args_of("f(1, ...)"), Some(vec![Number, Multiple]));
+        assert_eq!(args_of("f(..., 1)"), Some(vec![Unknown, Number]));
+        assert_eq!(args_of("f(1, 2"), None);
+    }
+
+    #[test]
+    fn lint_reports_literal_type_mismatches() {
+        assert_eq!(
+            lint("ctx.create_file(1, ...)"),
+            vec![
+                "main.luau:1:5: error: argument #1 'path' of ctx.create_file must be string, got number"
+            ]
+        );
+        assert_eq!(
+            lint(r#"ctx.create_file("a")"#),
+            vec!["main.luau:1:5: error: ctx.create_file expects 2 argument(s), got 1"]
+        );
+        assert!(lint(r#"ctx.create_file("a", content())"#).is_empty());
+        assert!(lint(r#"ctx.create_file(path, ...)"#).is_empty());
+    }
+
+    #[test]
+    fn lint_reports_colon_calls_and_unknown_members() {
+        assert_eq!(
+            lint(r#"ctx:log("info", "x")"#),
+            vec![
+                "main.luau:1:5: error: 'ctx:log' passes ctx as the first argument, call it as 'ctx.log'"
+            ]
+        );
+        assert_eq!(
+            lint("ctx.nope()\nlocal v = ctx.other"),
+            vec![
+                "main.luau:1:5: error: ctx has no member 'nope'",
+                "main.luau:2:15: warning: ctx has no member 'other'",
+            ]
+        );
+        assert!(lint("local r = ctx.monorepo.repo\nself.ctx:log()").is_empty());
+    }
+}
@@ -1,31 +1,160 @@
 use semver::Version;
-use serde::Deserialize;
-use std::{fs, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
-#[derive(Debug, Deserialize)]
+use crate::plugin::args::{CommandSpec, ExtendSpec};
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct PluginConfig {
     pub name: String,
     pub version: String,
     pub description: String,
+    #[serde(default)]
+    pub kind: PluginKind,
+    #[serde(default)]
     pub supported: Supported,
+    #[serde(default)]
+    pub requires: Requires,
+    /// Other plugins or libraries this one `require`s, e.g. `cargo-utils = "^0.1"`.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+    /// Global functions other plugins get from `require("@<name>")`.
+    #[serde(default)]
+    pub exports: Vec<String>,
+    /// Global function to call for each subscribed event, e.g.
+    /// `project_created = "on_project_created"`.
+    #[serde(default)]
+    pub hooks: BTreeMap<String, String>,
+    #[serde(default)]
+    pub extend: ExtendSpec,
+    /// Subcommands beyond the built-in actions, run as `apix plugin <name> <command>`.
+    #[serde(default)]
+    pub commands: BTreeMap<String, CommandSpec>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginKind {
+    #[default]
+    Plugin,
+    /// Only provides modules to other plugins, from `init.lua` and its folder.
+    Library,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Supported {
     pub actions: Vec<String>,
     pub languages: Vec<String>,
     pub features: Vec<String>,
 }
 
-/// Returns (PluginConfig, resolved_version)
-pub fn get_plugin_config(
-    plugins_dir: &PathBuf,
-    plugin_name: &str,
-    requested_version: &str,
-) -> Result<(PluginConfig, String), Box<dyn std::error::Error>> {
-    let plugin_dir = plugins_dir.join(plugin_name);
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Requires {
+    /// External tools the plugin shells out to, e.g. `cargo = ">= 1.80"`.
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolRequirement>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ToolRequirement {
+    Simple(String),
+    Detailed {
+        version: String,
+        /// Regex extracting the version from the output, the first capture
+        /// group is used if there is one.
+        regex: Option<String>,
+        /// Flag that prints the version, `--version` by default.
+        flag: Option<String>,
+    },
+}
+
+impl ToolRequirement {
+    pub fn version(&self) -> &str {
+        match self {
+            ToolRequirement::Simple(version) => version,
+            ToolRequirement::Detailed { version, .. } => version,
+        }
+    }
+
+    pub fn regex(&self) -> Option<&str> {
+        match self {
+            ToolRequirement::Simple(_) => None,
+            ToolRequirement::Detailed { regex, .. } => regex.as_deref(),
+        }
+    }
+
+    pub fn flag(&self) -> &str {
+        match self {
+            ToolRequirement::Detailed {
+                flag: Some(flag), ..
+            } => flag,
+            _ => "--version",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Dependency {
+    Simple(String),
+    Detailed {
+        version: String,
+        /// Folder to install the dependency from when no matching version is
+        /// installed, relative to the depending plugin.
+        path: Option<String>,
+    },
+}
+
+impl Dependency {
+    pub fn version(&self) -> &str {
+        match self {
+            Dependency::Simple(version) => version,
+            Dependency::Detailed { version, .. } => version,
+        }
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Dependency::Simple(_) => None,
+            Dependency::Detailed { path, .. } => path.as_deref(),
+        }
+    }
+}
+
+/// Reads `plugin.toml` from an installed version folder.
+pub fn read_plugin_config(version_dir: &Path) -> Result<PluginConfig, Box<dyn std::error::Error>> {
+    let path = version_dir.join("plugin.toml");
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    Ok(toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))?)
+}
+
+/// A plugin folder in `~/.apix/plugins` with the config of every installed version.
+#[derive(Debug)]
+pub struct InstalledPlugin {
+    pub name: String,
+    /// Sorted from oldest to newest.
+    pub versions: Vec<(Version, PluginConfig)>,
+}
 
-    let mut installed_versions: Vec<Version> = fs::read_dir(&plugin_dir)?
+impl InstalledPlugin {
+    pub fn latest(&self) -> Option<&PluginConfig> {
+        self.versions.last().map(|(_, config)| config)
+    }
+}
+
+/// Installed versions of a plugin, sorted from oldest to newest.
+pub fn installed_versions(
+    plugins_dir: &Path,
+    plugin_name: &str,
+) -> Result<Vec<Version>, Box<dyn std::error::Error>> {
+    let mut versions: Vec<Version> = fs::read_dir(plugins_dir.join(plugin_name))?
         .filter_map(|entry| {
             let entry = entry.ok()?;
             if entry.path().is_dir() {
@@ -35,35 +164,86 @@ pub fn get_plugin_config(
             }
         })
         .collect();
+    versions.sort();
 
-    if installed_versions.is_empty() {
-        return Err(format!("No installed versions found for plugin '{}'", plugin_name).into());
+    Ok(versions)
+}
+
+/// Every installed plugin, sorted by name. Versions without a readable
+/// `plugin.toml` are skipped.
+pub fn list_installed_plugins(
+    plugins_dir: &Path,
+) -> Result<Vec<InstalledPlugin>, Box<dyn std::error::Error>> {
+    let mut plugins = Vec::new();
+
+    for entry in fs::read_dir(plugins_dir)? {
+        let entry = entry?;
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        let versions = installed_versions(plugins_dir, &name)?
+            .into_iter()
+            .filter_map(|version| {
+                let config = read_plugin_config(&entry.path().join(version.to_string())).ok()?;
+                Some((version, config))
+            })
+            .collect::<Vec<_>>();
+
+        if !versions.is_empty() {
+            plugins.push(InstalledPlugin { name, versions });
+        }
     }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
 
-    installed_versions.sort();
+    Ok(plugins)
+}
 
-    let resolved_version = if requested_version == "*" {
-        installed_versions.last().unwrap().to_string()
+/// Picks the installed version used for `requested_version`: the exact match,
+/// else the next higher, else the next lower. `"*"` picks the newest.
+pub fn resolve_version(installed_versions: &[Version], requested_version: &str) -> Option<Version> {
+    if requested_version == "*" {
+        return installed_versions.last().cloned();
+    }
+
+    let req_ver = Version::parse(requested_version).ok()?;
+
+    if installed_versions.contains(&req_ver) {
+        Some(req_ver)
+    } else if let Some(next_highest) = installed_versions.iter().find(|v| **v > req_ver) {
+        Some(next_highest.clone())
     } else {
-        let req_ver = Version::parse(requested_version)
+        installed_versions
+            .iter()
+            .rev()
+            .find(|v| **v < req_ver)
+            .cloned()
+    }
+}
+
+/// Returns (PluginConfig, resolved_version)
+pub fn get_plugin_config(
+    plugins_dir: &PathBuf,
+    plugin_name: &str,
+    requested_version: &str,
+) -> Result<(PluginConfig, String), Box<dyn std::error::Error>> {
+    let plugin_dir = plugins_dir.join(plugin_name);
+
+    let installed_versions = installed_versions(plugins_dir, plugin_name)?;
+
+    if installed_versions.is_empty() {
+        return Err(format!("No installed versions found for plugin '{}'", plugin_name).into());
+    }
+
+    if requested_version != "*" {
+        Version::parse(requested_version)
             .map_err(|_| format!("Invalid version string '{}'", requested_version))?;
+    }
 
-        if installed_versions.contains(&req_ver) {
-            req_ver.to_string()
-        } else {
-            // pick next higher, if none, pick next lower
-            if let Some(next_highest) = installed_versions.iter().find(|v| **v > req_ver) {
-                next_highest.to_string()
-            } else {
-                installed_versions
-                    .iter()
-                    .rev()
-                    .find(|v| **v < req_ver)
-                    .unwrap()
-                    .to_string()
-            }
-        }
-    };
+    let resolved_version = resolve_version(&installed_versions, requested_version)
+        .unwrap()
+        .to_string();
 
     let plugin_config_path = plugin_dir.join(&resolved_version).join("plugin.toml");
     if !plugin_config_path.exists() {
@@ -74,8 +254,7 @@ pub fn get_plugin_config(
         .into());
     }
 
-    let plugin_config_str = fs::read_to_string(&plugin_config_path)?;
-    let plugin_config: PluginConfig = toml::from_str(&plugin_config_str)?;
+    let plugin_config = read_plugin_config(&plugin_dir.join(&resolved_version))?;
 
     Ok((plugin_config, resolved_version))
 }
@@ -0,0 +1,266 @@
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    plugin::{
+        config::{PluginConfig, installed_versions, read_plugin_config},
+        validate::{format_diagnostics, has_errors, validate_manifest},
+    },
+    utils::fs::copy_dir_recursive,
+};
+
+/// Lock file next to `monorepo.toml` pinning the dependency versions of every plugin.
+pub const LOCK_FILE: &str = "apix.lock";
+
+/// `[plugins.<plugin>]` tables mapping each dependency, direct or not, to a version.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub plugins: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Lockfile {
+    /// Reads `apix.lock` from `monorepo_root`, empty if there is none.
+    pub fn load(monorepo_root: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = monorepo_root.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::parse(&fs::read_to_string(&path)?)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(content).map_err(|e| format!("Invalid {}: {}", LOCK_FILE, e))?)
+    }
+
+    pub fn contents(&self) -> Result<String, Box<dyn Error>> {
+        Ok(format!(
+            "# Generated by apix, do not edit.\n\n{}",
+            toml::to_string(self)?
+        ))
+    }
+
+    pub fn save(&self, monorepo_root: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(monorepo_root.join(LOCK_FILE), self.contents()?)?;
+        Ok(())
+    }
+
+    /// Pins the dependencies of `plugin`. Returns `true` if the lock changed.
+    pub fn update(&mut self, plugin: &str, dependencies: BTreeMap<String, String>) -> bool {
+        if dependencies.is_empty() {
+            return self.plugins.remove(plugin).is_some();
+        }
+        if self.plugins.get(plugin) == Some(&dependencies) {
+            return false;
+        }
+        self.plugins.insert(plugin.to_string(), dependencies);
+        true
+    }
+}
+
+/// An installed dependency picked for a plugin.
+#[derive(Debug)]
+pub struct ResolvedDependency {
+    pub version: Version,
+    /// `<plugins>/<name>/<version>`.
+    pub dir: PathBuf,
+    pub config: PluginConfig,
+}
+
+/// Picks one installed version for every dependency of `root`, transitively,
+/// preferring the `locked` versions while they still match. Dependencies with
+/// a `path` are installed from it when no installed version matches.
+///
+/// Resolution is greedy and depth-first: the first requirement seen for a
+/// dependency selects its version and is never revisited. A later requirement
+/// that the selected version does not match fails with both requirement chains,
+/// even if another installed version would satisfy both.
+pub fn resolve_dependencies(
+    plugins_dir: &Path,
+    root_dir: &Path,
+    root: &PluginConfig,
+    locked: Option<&BTreeMap<String, String>>,
+) -> Result<BTreeMap<String, ResolvedDependency>, Box<dyn Error>> {
+    let mut resolved: BTreeMap<String, ResolvedDependency> = BTreeMap::new();
+    // Dependency name to the package and requirement that selected its version.
+    let mut selected_by: BTreeMap<String, (String, String)> = BTreeMap::new();
+    // (depending package, its folder, dependency name, requirement, install path)
+    let mut pending: Vec<(String, PathBuf, String, String, Option<String>)> =
+        dependency_queue(&root.name, root_dir, root);
+
+    while let Some((parent, parent_dir, name, requirement, path)) = pending.pop() {
+        if name == root.name {
+            return Err(format!("'{}' depends on itself through '{}'", root.name, parent).into());
+        }
+
+        let req = VersionReq::parse(&requirement).map_err(|e| {
+            format!(
+                "'{}' has an invalid version requirement '{}' for '{}': {}",
+                parent, requirement, name, e
+            )
+        })?;
+
+        if let Some(existing) = resolved.get(&name) {
+            if !req.matches(&existing.version) {
+                let (first_parent, first_requirement) = &selected_by[&name];
+                return Err(format!(
+                    "conflicting requirements for '{}': {} -> {} requires '{}', but {} -> {} \
+                     requires '{}' and selected {}. Dependencies are resolved without \
+                     backtracking, align the requirements",
+                    name,
+                    requirement_chain(&selected_by, &parent),
+                    name,
+                    requirement,
+                    requirement_chain(&selected_by, first_parent),
+                    name,
+                    first_requirement,
+                    existing.version
+                )
+                .into());
+            }
+            continue;
+        }
+
+        let installed = installed_versions(plugins_dir, &name).unwrap_or_default();
+        let locked_version = locked
+            .and_then(|l| l.get(&name))
+            .and_then(|v| Version::parse(v).ok())
+            .filter(|v| req.matches(v) && installed.contains(v));
+
+        // Folder the dependency's own `path`s are relative to.
+        let mut source_dir = None;
+        let version = match locked_version
+            .or_else(|| installed.iter().rev().find(|v| req.matches(v)).cloned())
+        {
+            Some(version) => version,
+            None => match &path {
+                Some(path) => {
+                    let src_dir = parent_dir.join(path);
+                    let version = install_from_path(&src_dir, plugins_dir, &name, &req)?;
+                    source_dir = Some(src_dir);
+                    version
+                }
+                None => {
+                    return Err(format!(
+                        "'{}' requires {} '{}', which is not installed",
+                        parent, name, requirement
+                    )
+                    .into());
+                }
+            },
+        };
+
+        let dir = plugins_dir.join(&name).join(version.to_string());
+        let diagnostics = validate_manifest(&dir);
+        if has_errors(&diagnostics) {
+            return Err(format_diagnostics(&name, &diagnostics).into());
+        }
+        let config = read_plugin_config(&dir)?;
+
+        pending.extend(dependency_queue(
+            &name,
+            source_dir.as_deref().unwrap_or(&dir),
+            &config,
+        ));
+        selected_by.insert(name.clone(), (parent, requirement));
+        resolved.insert(
+            name,
+            ResolvedDependency {
+                version,
+                dir,
+                config,
+            },
+        );
+    }
+
+    Ok(resolved)
+}
+
+/// `root -> a -> b`, the packages through which `package` was first required.
+fn requirement_chain(selected_by: &BTreeMap<String, (String, String)>, package: &str) -> String {
+    let mut chain = vec![package];
+    while let Some((parent, _)) = selected_by.get(*chain.last().unwrap()) {
+        chain.push(parent);
+    }
+    chain.reverse();
+
+    chain.join(" -> ")
+}
+
+fn dependency_queue(
+    parent: &str,
+    parent_dir: &Path,
+    config: &PluginConfig,
+) -> Vec<(String, PathBuf, String, String, Option<String>)> {
+    config
+        .dependencies
+        .iter()
+        .rev()
+        .map(|(name, dependency)| {
+            (
+                parent.to_string(),
+                parent_dir.to_path_buf(),
+                name.clone(),
+                dependency.version().to_string(),
+                dependency.path().map(str::to_string),
+            )
+        })
+        .collect()
+}
+
+/// Copies the plugin or library in `src_dir` to `<plugins>/<name>/<version>`.
+fn install_from_path(
+    src_dir: &Path,
+    plugins_dir: &Path,
+    name: &str,
+    req: &VersionReq,
+) -> Result<Version, Box<dyn Error>> {
+    let config = read_plugin_config(src_dir)?;
+    if config.name != name {
+        return Err(format!(
+            "'{}' contains '{}', expected '{}'",
+            src_dir.display(),
+            config.name,
+            name
+        )
+        .into());
+    }
+
+    let version = Version::parse(&config.version)
+        .map_err(|e| format!("'{}' has an invalid version: {}", src_dir.display(), e))?;
+    if !req.matches(&version) {
+        return Err(format!(
+            "'{}' is {} {}, which does not match '{}'",
+            src_dir.display(),
+            name,
+            version,
+            req
+        )
+        .into());
+    }
+
+    let dst_dir = plugins_dir.join(name).join(version.to_string());
+    if let Err(e) = copy_dir_recursive(src_dir, &dst_dir) {
+        let _ = fs::remove_dir_all(&dst_dir);
+        return Err(format!("Failed to install {} {}: {}", name, version, e).into());
+    }
+
+    Ok(version)
+}
+
+/// `name -> version` of a resolution, as stored in the lock file.
+pub fn locked_versions(
+    resolved: &BTreeMap<String, ResolvedDependency>,
+) -> BTreeMap<String, String> {
+    resolved
+        .iter()
+        .map(|(name, dependency)| (name.clone(), dependency.version.to_string()))
+        .collect()
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fs,
     io::{self, Read},
     path::{Path, PathBuf},
@@ -6,13 +7,51 @@ use std::{
 
 use mlua::{UserData, UserDataMethods};
 
+use crate::plugin::types::ApiFunction;
+
+pub const FILE_API: &[ApiFunction] = &[
+    ApiFunction {
+        name: "read",
+        signature: "() -> string",
+        doc: "Reads the file as UTF-8.",
+    },
+    ApiFunction {
+        name: "path",
+        signature: "() -> string",
+        doc: "Absolute path of the file.",
+    },
+];
+
+pub const DIR_API: &[ApiFunction] = &[
+    ApiFunction {
+        name: "entries",
+        signature: "() -> { LuaFile | LuaDir }",
+        doc: "Files and folders directly inside this folder.",
+    },
+    ApiFunction {
+        name: "path",
+        signature: "() -> string",
+        doc: "Absolute path of the folder.",
+    },
+];
+
+/// File changes not written to disk yet, keyed by path relative to the
+/// monorepo root: the new content, or `None` for a deleted file.
+pub type PendingFiles = BTreeMap<PathBuf, Option<String>>;
+
 #[derive(Debug, Clone)]
 pub struct LuaFile {
     pub path: PathBuf,
+    /// Pending content shadowing the file on disk.
+    pub content: Option<String>,
 }
 
 impl LuaFile {
     pub fn read(&self) -> io::Result<String> {
+        if let Some(content) = &self.content {
+            return Ok(content.clone());
+        }
+
         let mut file = fs::File::open(&self.path)?;
         let mut content = String::new();
         file.read_to_string(&mut content)?;
@@ -43,12 +82,64 @@ impl LuaDir {
             if entry.file_type()?.is_dir() {
                 entries.push(LuaEntry::Dir(LuaDir::scan(&entry_path)?));
             } else {
-                entries.push(LuaEntry::File(LuaFile { path: entry_path }));
+                entries.push(LuaEntry::File(LuaFile {
+                    path: entry_path,
+                    content: None,
+                }));
             }
         }
 
         Ok(LuaDir { path, entries })
     }
+
+    /// Like [`LuaDir::scan`], with `pending` changes laid over the files on disk.
+    pub fn scan_with_pending(path: impl AsRef<Path>, pending: &PendingFiles) -> io::Result<Self> {
+        let mut dir = Self::scan(path)?;
+        for (rel, content) in pending {
+            dir.apply_pending(rel, content.as_ref());
+        }
+
+        Ok(dir)
+    }
+
+    fn apply_pending(&mut self, rel: &Path, content: Option<&String>) {
+        let mut components = rel.components();
+        let Some(first) = components.next() else {
+            return;
+        };
+        let path = self.path.join(first);
+        let rest = components.as_path();
+
+        if rest.as_os_str().is_empty() {
+            self.entries
+                .retain(|entry| !matches!(entry, LuaEntry::File(f) if f.path == path));
+            if let Some(content) = content {
+                self.entries.push(LuaEntry::File(LuaFile {
+                    path,
+                    content: Some(content.clone()),
+                }));
+            }
+            return;
+        }
+
+        let existing = self.entries.iter_mut().find_map(|entry| match entry {
+            LuaEntry::Dir(dir) if dir.path == path => Some(dir),
+            _ => None,
+        });
+
+        match existing {
+            Some(dir) => dir.apply_pending(rest, content),
+            None if content.is_some() => {
+                let mut dir = LuaDir {
+                    path,
+                    entries: Vec::new(),
+                };
+                dir.apply_pending(rest, content);
+                self.entries.push(LuaEntry::Dir(dir));
+            }
+            None => {}
+        }
+    }
 }
 
 impl UserData for LuaFile {
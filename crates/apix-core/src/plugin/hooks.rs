@@ -0,0 +1,158 @@
+use regex::Regex;
+
+use crate::{
+    monorepo::config::UnitKind,
+    plugin::{config::PluginConfig, plan::Plan, plugin_ctx::ctx::CreatedUnit},
+};
+
+pub const PROJECT_CREATED: &str = "project_created";
+pub const PACKAGE_ADDED: &str = "package_added";
+pub const PLUGIN_UPDATED: &str = "plugin_updated";
+/// Prefix of `files_changed:<glob>`, matched against paths relative to the monorepo root.
+pub const FILES_CHANGED: &str = "files_changed:";
+
+/// What an applied plan did to the monorepo, to find the hooks it triggers.
+#[derive(Debug, Default)]
+pub struct AppliedChanges {
+    /// Plugin whose plan was applied.
+    pub plugin: String,
+    /// Projects and packages registered in monorepo.toml.
+    pub created: Vec<CreatedUnit>,
+    /// Plugin version bumped in monorepo.toml.
+    pub updated: Option<(String, String)>,
+    /// Files created, modified or deleted, relative to the monorepo root.
+    pub files: Vec<String>,
+}
+
+impl AppliedChanges {
+    /// Changes of `plan` as applied for `plugin`, which declared `created`.
+    pub fn from_plan(plugin: &str, plan: &Plan, created: Vec<CreatedUnit>) -> Self {
+        let files = plan
+            .file_changes()
+            .map(|changes| {
+                changes
+                    .keys()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            plugin: plugin.to_string(),
+            created,
+            updated: None,
+            files,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_none() && self.files.is_empty()
+    }
+}
+
+/// One call of a hook function, passed to it as the `event` table.
+#[derive(Debug)]
+pub struct HookEvent {
+    /// The `[hooks]` key that matched, e.g. `project_created` or `files_changed:**/*.rs`.
+    pub name: String,
+    /// Global function to call.
+    pub function: String,
+    /// Plugin whose plan raised the event.
+    pub source: String,
+    pub unit: Option<CreatedUnit>,
+    pub plugin: Option<(String, String)>,
+    pub files: Vec<String>,
+}
+
+/// Hook calls `config` subscribed to for `changes`, sorted by event. A plugin
+/// never reacts to its own changes.
+pub fn triggered_hooks(config: &PluginConfig, changes: &AppliedChanges) -> Vec<HookEvent> {
+    if config.name == changes.plugin {
+        return Vec::new();
+    }
+
+    let event = |name: &str, function: &str| HookEvent {
+        name: name.to_string(),
+        function: function.to_string(),
+        source: changes.plugin.clone(),
+        unit: None,
+        plugin: None,
+        files: Vec::new(),
+    };
+
+    let mut events = Vec::new();
+    for (name, function) in &config.hooks {
+        match name.as_str() {
+            PROJECT_CREATED | PACKAGE_ADDED => {
+                let kind = match name.as_str() {
+                    PROJECT_CREATED => UnitKind::Project,
+                    _ => UnitKind::Package,
+                };
+                for unit in changes.created.iter().filter(|u| u.kind == kind) {
+                    events.push(HookEvent {
+                        unit: Some(unit.clone()),
+                        ..event(name, function)
+                    });
+                }
+            }
+            PLUGIN_UPDATED => {
+                if let Some(updated) = &changes.updated {
+                    events.push(HookEvent {
+                        plugin: Some(updated.clone()),
+                        ..event(name, function)
+                    });
+                }
+            }
+            _ => {
+                let Some(glob) = name.strip_prefix(FILES_CHANGED) else {
+                    continue;
+                };
+                let pattern = glob_regex(glob);
+                let files: Vec<String> = changes
+                    .files
+                    .iter()
+                    .filter(|file| pattern.is_match(file))
+                    .cloned()
+                    .collect();
+                if !files.is_empty() {
+                    events.push(HookEvent {
+                        files,
+                        ..event(name, function)
+                    });
+                }
+            }
+        }
+    }
+
+    events
+}
+
+/// Compiles a glob: `*` and `?` stay within one path segment, `**` spans
+/// segments and `**/` also matches no folder at all.
+fn glob_regex(glob: &str) -> Regex {
+    let mut pattern = String::from("^");
+    let mut rest = glob;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**/") {
+            pattern.push_str("(?:.*/)?");
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("**") {
+            pattern.push_str(".*");
+            rest = after;
+            continue;
+        }
+
+        match c {
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).expect("Escaped glob is a valid regex")
+}
@@ -1,24 +1,52 @@
 use mlua::prelude::LuaTable;
 use mlua::{
-    Error as LuaError, FromLuaMulti, Function, IntoLuaMulti, Result as LuaResult, Value as LuaValue,
+    Error as LuaError, FromLuaMulti, Function, IntoLuaMulti, Lua, LuaSerdeExt, Result as LuaResult,
+    Value as LuaValue,
 };
+use std::collections::BTreeMap;
 
+use crate::plugin::args::{ArgSpec, ArgValue, ArgValues};
+use crate::plugin::config::PluginConfig;
+use crate::plugin::hooks::HookEvent;
 use crate::plugin::loader::LuaPlugin;
 use crate::plugin::plugin_ctx::info::PluginInfo;
 
 pub struct PluginInstance {
     lua_plugin: LuaPlugin,
+    /// `supported.actions` from plugin.toml; only these functions are called.
+    actions: Vec<String>,
+    /// Version picked for every dependency, for `apix.lock`.
+    dependencies: BTreeMap<String, String>,
 }
 
 impl PluginInstance {
-    pub fn new(lua_plugin: LuaPlugin) -> Self {
-        Self { lua_plugin }
+    pub fn new(
+        lua_plugin: LuaPlugin,
+        actions: Vec<String>,
+        dependencies: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            lua_plugin,
+            actions,
+            dependencies,
+        }
+    }
+
+    pub fn dependencies(&self) -> &BTreeMap<String, String> {
+        &self.dependencies
     }
 
     fn call_fn<T>(&self, fn_name: &str, args: impl IntoLuaMulti) -> LuaResult<T>
     where
         T: FromLuaMulti,
     {
+        if !self.actions.iter().any(|a| a == fn_name) {
+            return Err(mlua::Error::RuntimeError(format!(
+                "Plugin '{}' does not declare the '{}' action in plugin.toml",
+                self.lua_plugin.name, fn_name
+            )));
+        }
+
         let globals = self.lua_plugin.lua.globals();
 
         if !globals.contains_key(fn_name)? {
@@ -40,10 +68,62 @@ impl PluginInstance {
         self.call_fn("extend", args)
     }
 
+    /// Calls `extend` with arguments parsed against its declared schema.
+    pub fn extend_with(&self, args: &ArgValues) -> LuaResult<i32> {
+        self.call_fn("extend", args_table(&self.lua_plugin.lua, args)?)
+    }
+
+    /// Arguments `extend` declares, in plugin.toml or else in `info()`.
+    pub fn extend_args(&self, config: &PluginConfig) -> LuaResult<Vec<ArgSpec>> {
+        if !config.extend.args.is_empty() {
+            return Ok(config.extend.args.clone());
+        }
+        if !self.actions.iter().any(|a| a == "info") {
+            return Ok(Vec::new());
+        }
+
+        Ok(self.info()?.map(|info| info.args).unwrap_or_default())
+    }
+
     pub fn migrate(&self, from_version: String) -> LuaResult<i32> {
         self.call_fn("migrate", from_version)
     }
 
+    /// Calls the hook function of `event` with the `event` table.
+    pub fn hook(&self, event: &HookEvent) -> LuaResult<i32> {
+        let lua = &self.lua_plugin.lua;
+        let table = lua.create_table()?;
+        table.set("name", event.name.as_str())?;
+        table.set("source", event.source.as_str())?;
+
+        if let Some(unit) = &event.unit {
+            let unit_table = lua.create_table()?;
+            unit_table.set("name", unit.name.as_str())?;
+            unit_table.set("kind", unit.kind.as_str())?;
+            unit_table.set("path", unit.path.as_str())?;
+            unit_table.set("language", unit.language.as_str())?;
+            table.set("unit", unit_table)?;
+        }
+        if let Some((name, version)) = &event.plugin {
+            let plugin_table = lua.create_table()?;
+            plugin_table.set("name", name.as_str())?;
+            plugin_table.set("version", version.as_str())?;
+            table.set("plugin", plugin_table)?;
+        }
+        table.set("files", event.files.clone())?;
+
+        let func: Function = lua.globals().get(event.function.as_str())?;
+        func.call::<i32>(table)
+    }
+
+    /// Calls the global `function` of a command declared in plugin.toml with
+    /// its parsed arguments.
+    pub fn command(&self, function: &str, args: &ArgValues) -> LuaResult<i32> {
+        let lua = &self.lua_plugin.lua;
+        let func: Function = lua.globals().get(function)?;
+        func.call::<i32>(args_table(lua, args)?)
+    }
+
     pub fn info(&self) -> Result<Option<PluginInfo>, LuaError> {
         let table: Option<LuaTable> = match self.call_fn("info", ())? {
             LuaValue::Table(t) => Some(t),
@@ -59,19 +139,76 @@ impl PluginInstance {
 
         match table {
             Some(tbl) => {
-                let usage: Vec<String> = tbl.get("usage")?;
-                let options_table: LuaTable = tbl.get("options")?;
+                let usage: Vec<String> = tbl.get::<Option<_>>("usage")?.unwrap_or_default();
                 let mut options = Vec::new();
-                for pair in options_table.sequence_values::<LuaTable>() {
-                    let pair_table = pair?;
-                    let opt_name: String = pair_table.get(1)?;
-                    let opt_desc: String = pair_table.get(2)?;
-                    options.push((opt_name, opt_desc));
+                if let Some(options_table) = tbl.get::<Option<LuaTable>>("options")? {
+                    for pair in options_table.sequence_values::<LuaTable>() {
+                        let pair_table = pair?;
+                        let opt_name: String = pair_table.get(1)?;
+                        let opt_desc: String = pair_table.get(2)?;
+                        options.push((opt_name, opt_desc));
+                    }
+                }
+                let mut args = Vec::new();
+                if let Some(args_table) = tbl.get::<Option<LuaTable>>("args")? {
+                    for arg in args_table.sequence_values::<LuaTable>() {
+                        args.push(arg_spec(&self.lua_plugin.lua, arg?)?);
+                    }
                 }
 
-                Ok(Some(PluginInfo { usage, options }))
+                Ok(Some(PluginInfo {
+                    usage,
+                    options,
+                    args,
+                }))
             }
             None => Ok(None),
         }
     }
 }
+
+fn args_table(lua: &Lua, args: &ArgValues) -> LuaResult<LuaTable> {
+    let table = lua.create_table()?;
+    for (name, value) in args {
+        match value {
+            ArgValue::Bool(value) => table.set(name.as_str(), *value)?,
+            ArgValue::Int(value) => table.set(name.as_str(), *value)?,
+            ArgValue::String(value) => table.set(name.as_str(), value.as_str())?,
+        }
+    }
+
+    Ok(table)
+}
+
+/// Reads an entry of `info().args`. Luau numbers are floats, so whole
+/// numbers are taken as int defaults.
+fn arg_spec(lua: &Lua, table: LuaTable) -> LuaResult<ArgSpec> {
+    let fields = lua.create_table()?;
+    let mut default = None;
+    for pair in table.pairs::<String, LuaValue>() {
+        let (key, value) = pair?;
+        if key != "default" {
+            fields.set(key, value)?;
+            continue;
+        }
+
+        default = Some(match value {
+            LuaValue::Boolean(value) => ArgValue::Bool(value),
+            LuaValue::Integer(value) => ArgValue::Int(value),
+            LuaValue::Number(value) if value.fract() == 0.0 => ArgValue::Int(value as i64),
+            LuaValue::String(value) => ArgValue::String(value.to_str()?.to_string()),
+            other => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: other.type_name(),
+                    to: "default".to_string(),
+                    message: Some("Expected a string, integer or boolean default".to_string()),
+                });
+            }
+        });
+    }
+
+    let mut spec: ArgSpec = lua.from_value(LuaValue::Table(fields))?;
+    spec.default = default;
+
+    Ok(spec)
+}
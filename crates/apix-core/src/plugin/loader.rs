@@ -1,12 +1,39 @@
 use mlua::{Lua, Result};
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
     rc::Rc,
 };
 
-use crate::plugin::{file_tree::LuaDir, plugin_ctx::ctx::PluginCtx};
+use crate::{
+    monorepo::config::{MonorepoConfig, get_monorepo_config},
+    plugin::{
+        config::PluginConfig,
+        dependencies::ResolvedDependency,
+        file_tree::LuaDir,
+        modules::{ModuleLoader, register_require},
+        plugin_ctx::{ctx::PluginCtx, monorepo::create_monorepo_table},
+    },
+};
+
+/// `<name>.lua`, or `<name>.luau` if there is no `.lua` file, in `plugin_dir`.
+pub fn entry_path(plugin_dir: &Path, name: &str) -> Result<PathBuf> {
+    let lua_path = plugin_dir.join(format!("{}.lua", name));
+    let luau_path = plugin_dir.join(format!("{}.luau", name));
+
+    if lua_path.exists() {
+        Ok(lua_path)
+    } else if luau_path.exists() {
+        Ok(luau_path)
+    } else {
+        Err(mlua::Error::RuntimeError(format!(
+            "Plugin {} does not exist at either {:?} or {:?}",
+            name, lua_path, luau_path
+        )))
+    }
+}
 
 pub struct LuaPlugin {
     pub name: String,
@@ -20,22 +47,16 @@ impl LuaPlugin {
         plugin_version: &str,
         monorepo_root: &PathBuf,
         ctx: Rc<RefCell<PluginCtx>>,
+        config: &PluginConfig,
+        dependencies: &BTreeMap<String, ResolvedDependency>,
     ) -> Result<Self> {
-        let lua_path = plugins_dir.join(format!("{}/{}/{}.lua", name, plugin_version, name));
-        let luau_path = plugins_dir.join(format!("{}/{}/{}.luau", name, plugin_version, name));
-
-        let plugin_path = if lua_path.exists() {
-            lua_path
-        } else if luau_path.exists() {
-            luau_path
-        } else {
-            return Err(mlua::Error::RuntimeError(format!(
-                "Plugin {} does not exist at either {:?} or {:?}",
-                name, lua_path, luau_path
-            )));
-        };
+        let plugin_dir = plugins_dir.join(name).join(plugin_version);
+        let plugin_path = entry_path(&plugin_dir, name)?;
 
         let lua = Lua::new();
+        let mut modules = ModuleLoader::new(&plugin_dir, config)?;
+        modules.add_dependencies(&lua, dependencies)?;
+        register_require(&lua, Rc::new(RefCell::new(modules)))?;
 
         let data_dir = plugins_dir.join(format!("{}/{}/data", name, plugin_version));
         if !data_dir.exists() {
@@ -47,20 +68,40 @@ impl LuaPlugin {
         let canon_monorepo_root =
             fs::canonicalize(&monorepo_root).expect("Failed to canonicalize monorepo root");
 
-        let root_dir = LuaDir::scan(&canon_monorepo_root)?;
+        let pending = ctx.borrow().pending.clone();
+        let root_dir = LuaDir::scan_with_pending(&canon_monorepo_root, &pending)?;
         let root_dir_monorepodata = lua.create_userdata(root_dir)?;
         let data_dir = LuaDir::scan(&canon_plugin_data_dir)?;
         let data_dir_plugindata = lua.create_userdata(data_dir)?;
 
         let lua_ctx_table = PluginCtx::register(&lua, ctx.clone())?;
 
+        // A monorepo.toml changed by earlier recipe steps wins over the one on disk.
+        let monorepo_config: Option<std::result::Result<MonorepoConfig, String>> =
+            match pending.get(Path::new("monorepo.toml")) {
+                Some(Some(content)) => Some(toml::from_str(content).map_err(|e| e.to_string())),
+                _ if monorepo_root.join("monorepo.toml").exists() => {
+                    Some(get_monorepo_config(monorepo_root).map_err(|e| e.to_string()))
+                }
+                _ => None,
+            };
+        if let Some(monorepo_config) = monorepo_config {
+            let monorepo_config = monorepo_config
+                .map_err(|e| mlua::Error::RuntimeError(format!("Invalid monorepo.toml: {}", e)))?;
+            let monorepo_table =
+                create_monorepo_table(&lua, &monorepo_config, ctx.borrow().target.as_ref())?;
+            lua_ctx_table.set("monorepo", monorepo_table)?;
+        }
+
         let globals = lua.globals();
         globals.set("ctx", lua_ctx_table)?;
         globals.set("monorepo_root_dir", root_dir_monorepodata)?;
         globals.set("plugin_data_dir", data_dir_plugindata)?;
 
         let plugin_code = fs::read_to_string(&plugin_path)?;
-        lua.load(&plugin_code).exec()?;
+        lua.load(&plugin_code)
+            .set_name(format!("@{}", plugin_path.display()))
+            .exec()?;
 
         Ok(Self {
             name: name.to_string(),
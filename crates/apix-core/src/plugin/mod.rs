@@ -1,7 +1,17 @@
+pub mod args;
+pub mod check;
 pub mod config;
+pub mod dependencies;
 pub mod file_tree;
+pub mod hooks;
 pub mod instance;
 pub mod loader;
+pub mod modules;
 pub mod plan;
 pub mod plugin_ctx;
+pub mod scaffold;
+pub mod testing;
+pub mod tools;
+pub mod types;
 pub mod utils;
+pub mod validate;
@@ -0,0 +1,352 @@
+use log::warn;
+use mlua::{Lua, Result as LuaResult, Table as LuaTable, Value as LuaValue};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::plugin::{
+    check::{check_file, is_strict},
+    config::{PluginConfig, PluginKind},
+    dependencies::ResolvedDependency,
+    loader::entry_path,
+    validate::{format_diagnostics, has_errors},
+};
+
+/// Folder inside a plugin holding vendored third-party modules.
+pub const LIB_DIR: &str = "lib";
+
+const EXTENSIONS: &[&str] = &["luau", "lua"];
+
+/// The plugin being run, or one of its dependencies.
+struct Package {
+    name: String,
+    dir: PathBuf,
+    /// File `require("@<name>")` runs: a plugin's entry file or a library's `init`.
+    entry: Option<PathBuf>,
+    exports: Vec<String>,
+    /// Names this package may `require("@<name>/...")`.
+    dependencies: Vec<String>,
+    /// Globals of a dependency, `None` for the plugin being run.
+    env: Option<LuaTable>,
+}
+
+/// Resolves and runs `require`d modules, never leaving the folder of the
+/// package that requires them.
+pub struct ModuleLoader {
+    packages: HashMap<String, Package>,
+    loaded: HashMap<PathBuf, LuaValue>,
+    /// Modules currently executing, to report cycles.
+    loading: Vec<PathBuf>,
+}
+
+impl ModuleLoader {
+    pub fn new(plugin_dir: &Path, config: &PluginConfig) -> std::io::Result<Self> {
+        let mut loader = Self {
+            packages: HashMap::new(),
+            loaded: HashMap::new(),
+            loading: Vec::new(),
+        };
+        loader.add_package(plugin_dir, config, None)?;
+        // The entry file runs without `require`, requiring it again is a cycle.
+        if let Some(entry) = loader.packages[&config.name].entry.clone() {
+            loader.loading.push(entry);
+        }
+
+        Ok(loader)
+    }
+
+    /// Makes the resolved dependencies available to `require("@<name>")`, each
+    /// running with its own globals that fall back to the plugin's.
+    pub fn add_dependencies(
+        &mut self,
+        lua: &Lua,
+        dependencies: &BTreeMap<String, ResolvedDependency>,
+    ) -> LuaResult<()> {
+        for dependency in dependencies.values() {
+            let env = lua.create_table()?;
+            let fallback = lua.create_table()?;
+            fallback.set("__index", lua.globals())?;
+            env.set_metatable(Some(fallback))?;
+
+            self.add_package(&dependency.dir, &dependency.config, Some(env))?;
+        }
+
+        Ok(())
+    }
+
+    fn add_package(
+        &mut self,
+        dir: &Path,
+        config: &PluginConfig,
+        env: Option<LuaTable>,
+    ) -> std::io::Result<()> {
+        let dir = fs::canonicalize(dir)?;
+        let entry = match config.kind {
+            PluginKind::Plugin => entry_path(&dir, &config.name).ok(),
+            PluginKind::Library => find_module(&dir, Path::new("init")),
+        };
+
+        self.packages.insert(
+            config.name.clone(),
+            Package {
+                name: config.name.clone(),
+                dir,
+                entry,
+                exports: config.exports.clone(),
+                dependencies: config.dependencies.keys().cloned().collect(),
+                env,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Finds the file for `name` as required from `package`. Plain names like
+    /// `helpers.cargo`, `helpers/cargo` or `./helpers/cargo` try `<name>.luau`,
+    /// `<name>.lua` and `<name>/init.*` in the package folder, then in `lib/`.
+    /// `@dep/module` does the same in the folder of the dependency `dep`, and
+    /// `@dep` alone is its entry file. Returns the owning package and the file.
+    pub fn resolve(&self, package: &str, name: &str) -> Result<(String, PathBuf), String> {
+        let Some(aliased) = name.strip_prefix('@') else {
+            let path = self.resolve_in(&self.packages[package], name, name)?;
+            return Ok((package.to_string(), path));
+        };
+
+        let (dependency, module) = match aliased.split_once('/') {
+            Some((dependency, module)) => (dependency, Some(module)),
+            None => (aliased, None),
+        };
+
+        if !self.packages[package]
+            .dependencies
+            .iter()
+            .any(|d| d == dependency)
+        {
+            return Err(format!(
+                "'{}' is not a dependency of '{}', add it to [dependencies] in plugin.toml",
+                dependency, package
+            ));
+        }
+        let target = &self.packages[dependency];
+
+        let path = match module {
+            Some(module) => self.resolve_in(target, module, name)?,
+            None => target
+                .entry
+                .clone()
+                .ok_or_else(|| format!("'{}' has no entry file or init module", dependency))?,
+        };
+
+        Ok((dependency.to_string(), path))
+    }
+
+    fn resolve_in(&self, package: &Package, module: &str, name: &str) -> Result<PathBuf, String> {
+        let relative = module_path(module).map_err(|e| format!("module '{}' {}", name, e))?;
+
+        for root in [package.dir.clone(), package.dir.join(LIB_DIR)] {
+            let Some(candidate) = find_module(&root, &relative) else {
+                continue;
+            };
+            let resolved = fs::canonicalize(&candidate).map_err(|e| e.to_string())?;
+            if !resolved.starts_with(&package.dir) {
+                return Err(format!(
+                    "module '{}' resolves outside the '{}' folder to '{}'",
+                    name,
+                    package.name,
+                    resolved.display()
+                ));
+            }
+            return Ok(resolved);
+        }
+
+        Err(format!(
+            "module '{}' not found in the '{}' folder or its {}/ folder",
+            name, package.name, LIB_DIR
+        ))
+    }
+
+    fn display(&self, path: &Path) -> String {
+        self.packages
+            .values()
+            .find_map(|package| {
+                let relative = path.strip_prefix(&package.dir).ok()?;
+                Some(match &package.env {
+                    Some(_) => format!("@{}/{}", package.name, relative.display()),
+                    None => relative.display().to_string(),
+                })
+            })
+            .unwrap_or_else(|| path.display().to_string())
+    }
+
+    /// Table of a dependency's exported functions, read from its globals after
+    /// its entry file ran.
+    fn exports(&self, lua: &Lua, package: &str) -> LuaResult<Option<LuaTable>> {
+        let package = &self.packages[package];
+        let (Some(env), false) = (&package.env, package.exports.is_empty()) else {
+            return Ok(None);
+        };
+
+        let table = lua.create_table()?;
+        for name in &package.exports {
+            match env.get::<LuaValue>(name.as_str())? {
+                LuaValue::Function(function) => table.set(name.as_str(), function)?,
+                _ => {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "'{}' exports '{}' but does not define a function with that name",
+                        package.name, name
+                    )));
+                }
+            }
+        }
+
+        Ok(Some(table))
+    }
+}
+
+fn find_module(root: &Path, relative: &Path) -> Option<PathBuf> {
+    let base = root.join(relative);
+    let files = EXTENSIONS
+        .iter()
+        .map(|ext| PathBuf::from(format!("{}.{}", base.display(), ext)));
+    let inits = EXTENSIONS
+        .iter()
+        .map(|ext| base.join(format!("init.{}", ext)));
+
+    files.chain(inits).find(|candidate| candidate.is_file())
+}
+
+/// Turns a module name into a relative path, rejecting anything that could
+/// escape the package folder.
+fn module_path(name: &str) -> Result<PathBuf, String> {
+    let trimmed = name.strip_prefix("./").unwrap_or(name);
+    if trimmed.is_empty() {
+        return Err("is empty".to_string());
+    }
+
+    // `a.b` is the classic Lua spelling of `a/b`.
+    let path = if trimmed.contains('/') {
+        PathBuf::from(trimmed)
+    } else {
+        PathBuf::from(trimmed.replace('.', "/"))
+    };
+
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err("must be a path inside the package folder".to_string());
+    }
+
+    Ok(path)
+}
+
+/// Replaces the global `require` of the plugin and sets one in the globals of
+/// each of its dependencies, all backed by `loader`.
+pub fn register_require(lua: &Lua, loader: Rc<RefCell<ModuleLoader>>) -> LuaResult<()> {
+    let envs: Vec<(String, Option<LuaTable>)> = loader
+        .borrow()
+        .packages
+        .values()
+        .map(|package| (package.name.clone(), package.env.clone()))
+        .collect();
+
+    for (name, env) in envs {
+        let require_fn = create_require(lua, name.clone(), loader.clone())?;
+        match env {
+            Some(env) => env.raw_set("require", require_fn)?,
+            None => lua.globals().set("require", require_fn)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn create_require(
+    lua: &Lua,
+    package: String,
+    loader: Rc<RefCell<ModuleLoader>>,
+) -> LuaResult<mlua::Function> {
+    lua.create_function(move |lua, name: String| {
+        let (owner, path) = loader
+            .borrow()
+            .resolve(&package, &name)
+            .map_err(mlua::Error::RuntimeError)?;
+
+        if let Some(value) = loader.borrow().loaded.get(&path) {
+            return Ok(value.clone());
+        }
+
+        let env = {
+            let mut loader = loader.borrow_mut();
+            if let Some(start) = loader.loading.iter().position(|p| p == &path) {
+                let cycle: Vec<String> = loader.loading[start..]
+                    .iter()
+                    .chain([&path])
+                    .map(|p| loader.display(p))
+                    .collect();
+                return Err(mlua::Error::RuntimeError(format!(
+                    "require cycle: {}",
+                    cycle.join(" -> ")
+                )));
+            }
+            loader.loading.push(path.clone());
+            loader.packages[&owner].env.clone()
+        };
+
+        // The loader must not stay borrowed while the module runs, it may require others.
+        let result = fs::read_to_string(&path)
+            .map_err(mlua::Error::external)
+            .and_then(|source| {
+                check_strict(&owner, &path, &source)?;
+                let chunk = lua.load(source).set_name(format!("@{}", path.display()));
+                match env {
+                    Some(env) => chunk.set_environment(env),
+                    None => chunk,
+                }
+                .call::<LuaValue>(())
+            });
+
+        let mut loader = loader.borrow_mut();
+        loader.loading.pop();
+
+        let mut value = match result? {
+            LuaValue::Nil => LuaValue::Boolean(true),
+            value => value,
+        };
+        if name.starts_with('@')
+            && !name.contains('/')
+            && let Some(exports) = loader.exports(lua, &owner)?
+        {
+            value = LuaValue::Table(exports);
+        }
+        loader.loaded.insert(path, value.clone());
+
+        Ok(value)
+    })
+}
+
+/// Runs the load-time check of the entry file on modules that start with `--!strict`.
+fn check_strict(package: &str, path: &Path, source: &str) -> LuaResult<()> {
+    if !is_strict(source) {
+        return Ok(());
+    }
+
+    let (analyzer, diagnostics) =
+        check_file(path, source).map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
+    if has_errors(&diagnostics) {
+        return Err(mlua::Error::RuntimeError(format!(
+            "{}\n  ({})",
+            format_diagnostics(package, &diagnostics),
+            analyzer.scope()
+        )));
+    }
+    for diagnostic in &diagnostics {
+        warn!("Plugin '{}': {}", package, diagnostic);
+    }
+
+    Ok(())
+}
@@ -1,4 +1,14 @@
-use crate::plugin::plugin_ctx::ctx::Proposal;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+use tempfile::TempDir;
+
+use crate::plugin::{file_tree::PendingFiles, plugin_ctx::ctx::Proposal};
 
 pub struct Plan {
     pub proposals: Vec<Proposal>,
@@ -9,7 +19,252 @@ impl Plan {
         Self { proposals }
     }
 
-    pub fn validate() {}
+    pub fn is_empty(&self) -> bool {
+        self.proposals.is_empty()
+    }
+
+    /// Checks every proposal against the current monorepo tree, taking the
+    /// effect of earlier proposals in the same plan into account.
+    pub fn validate(&self, monorepo_root: &Path) -> Result<(), Box<dyn Error>> {
+        let mut pending: BTreeMap<PathBuf, bool> = BTreeMap::new();
+
+        for proposal in &self.proposals {
+            match proposal {
+                Proposal::CreateFile { path, .. } => {
+                    let rel = relative_path(path)?;
+                    let exists = *pending
+                        .get(&rel)
+                        .unwrap_or(&monorepo_root.join(&rel).exists());
+                    if exists {
+                        return Err(format!("Cannot create '{}': file already exists", path).into());
+                    }
+                    pending.insert(rel, true);
+                }
+                Proposal::ModifyFile { path, .. } => {
+                    let rel = relative_path(path)?;
+                    let exists = *pending
+                        .get(&rel)
+                        .unwrap_or(&monorepo_root.join(&rel).is_file());
+                    if !exists {
+                        return Err(format!("Cannot modify '{}': file does not exist", path).into());
+                    }
+                }
+                Proposal::DeleteFile { path } => {
+                    let rel = relative_path(path)?;
+                    let exists = *pending
+                        .get(&rel)
+                        .unwrap_or(&monorepo_root.join(&rel).is_file());
+                    if !exists {
+                        return Err(format!("Cannot delete '{}': file does not exist", path).into());
+                    }
+                    pending.insert(rel, false);
+                }
+                Proposal::SystemCommand { command, .. } => {
+                    if command.trim().is_empty() {
+                        return Err("Proposed system command is empty".into());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rejects file proposals outside `scope`, a path relative to the monorepo
+    /// root, and makes system commands run in it.
+    pub fn confine_to(&mut self, scope: &str) -> Result<(), Box<dyn Error>> {
+        let scope = relative_path(scope)?;
+
+        for proposal in &mut self.proposals {
+            let path = match proposal {
+                Proposal::CreateFile { path, .. }
+                | Proposal::ModifyFile { path, .. }
+                | Proposal::DeleteFile { path } => path,
+                Proposal::SystemCommand { dir, .. } => {
+                    *dir = Some(scope.to_string_lossy().to_string());
+                    continue;
+                }
+            };
+
+            if !relative_path(path)?.starts_with(&scope) {
+                return Err(format!(
+                    "'{}' is outside of the target path '{}'",
+                    path,
+                    scope.display()
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lines describing the plan in the style of `git status --short`.
+    pub fn summary(&self) -> Vec<String> {
+        self.proposals
+            .iter()
+            .map(|proposal| match proposal {
+                Proposal::CreateFile { path, .. } => format!("A  {}", path),
+                Proposal::ModifyFile { path, .. } => format!("M  {}", path),
+                Proposal::DeleteFile { path } => format!("D  {}", path),
+                Proposal::SystemCommand { command, args, dir } => {
+                    let line = format!("$  {} {}", command, args.join(" "))
+                        .trim_end()
+                        .to_string();
+                    match dir {
+                        Some(dir) => format!("{}  (in {})", line, dir),
+                        None => line,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Final state of every touched file: `Some(content)` to write, `None` to delete.
+    pub fn file_changes(&self) -> Result<PendingFiles, Box<dyn Error>> {
+        let mut changes = BTreeMap::new();
+
+        for proposal in &self.proposals {
+            match proposal {
+                Proposal::CreateFile { path, content } | Proposal::ModifyFile { path, content } => {
+                    changes.insert(relative_path(path)?, Some(content.clone()));
+                }
+                Proposal::DeleteFile { path } => {
+                    changes.insert(relative_path(path)?, None);
+                }
+                Proposal::SystemCommand { .. } => {}
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Writes the resulting files into a temporary folder so that write errors
+    /// surface before the monorepo is touched.
+    pub fn apply_to_tmp(&self) -> Result<TempDir, Box<dyn Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+
+        for (rel, content) in self.file_changes()? {
+            if let Some(content) = content {
+                let staged = tmp_dir.path().join(&rel);
+                if let Some(parent) = staged.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&staged, content)?;
+            }
+        }
+
+        Ok(tmp_dir)
+    }
+
+    /// Applies the plan to the monorepo. File changes are staged first and
+    /// rolled back if any write or system command fails.
+    pub fn apply(&self, monorepo_root: &Path) -> Result<(), Box<dyn Error>> {
+        self.validate(monorepo_root)?;
+
+        let changes = self.file_changes()?;
+        let staging = self.apply_to_tmp()?;
+
+        let mut backups: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for rel in changes.keys() {
+            let target = monorepo_root.join(rel);
+            let original = if target.is_file() {
+                Some(fs::read(&target)?)
+            } else {
+                None
+            };
+            backups.push((target, original));
+        }
+
+        let result = commit_changes(monorepo_root, staging.path(), &changes)
+            .and_then(|_| self.run_system_commands(monorepo_root));
+
+        if let Err(e) = result {
+            rollback(&backups);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    fn run_system_commands(&self, monorepo_root: &Path) -> Result<(), Box<dyn Error>> {
+        for proposal in &self.proposals {
+            if let Proposal::SystemCommand { command, args, dir } = proposal {
+                let work_dir = match dir {
+                    Some(dir) => monorepo_root.join(relative_path(dir)?),
+                    None => monorepo_root.to_path_buf(),
+                };
+                let status = Command::new(command)
+                    .args(args)
+                    .current_dir(work_dir)
+                    .status()
+                    .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+
+                if !status.success() {
+                    return Err(format!(
+                        "Command '{} {}' failed with {}",
+                        command,
+                        args.join(" "),
+                        status
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn commit_changes(
+    monorepo_root: &Path,
+    staging_dir: &Path,
+    changes: &BTreeMap<PathBuf, Option<String>>,
+) -> Result<(), Box<dyn Error>> {
+    for (rel, content) in changes {
+        let target = monorepo_root.join(rel);
+
+        match content {
+            Some(_) => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(staging_dir.join(rel), &target)?;
+            }
+            None => fs::remove_file(&target)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn rollback(backups: &[(PathBuf, Option<Vec<u8>>)]) {
+    for (target, original) in backups.iter().rev() {
+        let _ = match original {
+            Some(bytes) => fs::write(target, bytes),
+            None if target.exists() => fs::remove_file(target),
+            None => Ok(()),
+        };
+    }
+}
+
+/// Normalizes a proposal path and rejects anything escaping the monorepo root.
+pub fn relative_path(path: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let mut rel = PathBuf::new();
+
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => rel.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(format!("Path '{}' must stay inside the monorepo root", path).into());
+            }
+        }
+    }
+
+    if rel.as_os_str().is_empty() {
+        return Err("Proposed file path is empty".into());
+    }
 
-    pub fn apply_to_tmp() {}
+    Ok(rel)
 }
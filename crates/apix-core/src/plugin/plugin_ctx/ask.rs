@@ -4,6 +4,13 @@ use std::io::{self, Write};
 use std::rc::Rc;
 
 use crate::plugin::plugin_ctx::ctx::PluginCtx;
+use crate::plugin::types::ApiFunction;
+
+pub const API: &[ApiFunction] = &[ApiFunction {
+    name: "ask",
+    signature: "(question: string) -> string",
+    doc: "Prints `question` and returns the trimmed answer.",
+}];
 
 pub fn register_ask_function(
     lua: &Lua,
@@ -17,6 +24,15 @@ pub fn register_ask_function(
         let log_fn: LuaFunction = ask_table.get("info")?;
         log_fn.call::<()>(format!("> {}", question))?;
 
+        let scripted = ask_ctx.borrow_mut().answers.as_mut().map(|a| a.pop_front());
+        if let Some(answer) = scripted {
+            let answer = answer.ok_or_else(|| {
+                LuaError::RuntimeError(format!("No scripted answer left for '{}'", question))
+            })?;
+            ask_ctx.borrow_mut().logs.push(format!("[ask] {}", answer));
+            return Ok(answer);
+        }
+
         print!("> ");
         io::stdout().flush().unwrap();
 
@@ -1,23 +1,60 @@
 use mlua::prelude::*;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::rc::Rc;
 
+use crate::events::EventHistory;
+use crate::monorepo::config::{MonorepoTarget, UnitKind};
+use crate::plugin::file_tree::PendingFiles;
 use crate::plugin::plugin_ctx::logger::PluginLogger;
-use crate::plugin::plugin_ctx::{ask, files, logger, system};
+use crate::plugin::plugin_ctx::{ask, events, files, logger, monorepo, system};
 
 #[derive(Debug)]
 pub enum Proposal {
-    CreateFile { path: String, content: String },
-    ModifyFile { path: String, content: String },
-    DeleteFile { path: String },
-    SystemCommand { command: String, args: Vec<String> },
+    CreateFile {
+        path: String,
+        content: String,
+    },
+    ModifyFile {
+        path: String,
+        content: String,
+    },
+    DeleteFile {
+        path: String,
+    },
+    SystemCommand {
+        command: String,
+        args: Vec<String>,
+        /// Folder to run in, relative to the monorepo root. `None` runs in the root.
+        dir: Option<String>,
+    },
+}
+
+/// A project or package a plugin reports as created by its proposals.
+#[derive(Debug, Clone)]
+pub struct CreatedUnit {
+    pub name: String,
+    pub kind: UnitKind,
+    pub path: String,
+    pub language: String,
 }
 
 #[derive(Debug)]
 pub struct PluginCtx {
     pub logs: Vec<String>,
     pub proposals: Vec<Proposal>,
+    pub created: Vec<CreatedUnit>,
     pub logger: Rc<RefCell<PluginLogger>>,
+    pub history: Option<EventHistory>,
+    pub target: Option<MonorepoTarget>,
+    /// Scripted answers for `ctx.ask`, consumed in order instead of reading stdin.
+    pub answers: Option<VecDeque<String>>,
+    /// Changes proposed by earlier steps of a recipe, visible through
+    /// `monorepo_root_dir` and `ctx.monorepo`.
+    pub pending: PendingFiles,
+    /// `apix.lock` pinning the dependencies this load resolved, when that
+    /// changes it. Only written together with the plan of the call.
+    pub lock: Option<String>,
 }
 
 impl PluginCtx {
@@ -25,7 +62,13 @@ impl PluginCtx {
         Self {
             logs: Vec::new(),
             proposals: Vec::new(),
+            created: Vec::new(),
             logger: Rc::new(RefCell::new(PluginLogger::new(plugin_name))),
+            history: None,
+            target: None,
+            answers: None,
+            pending: PendingFiles::new(),
+            lock: None,
         }
     }
 
@@ -36,6 +79,8 @@ impl PluginCtx {
         ask::register_ask_function(lua, ctx.clone(), &table)?;
         files::register_file_functions(lua, ctx.clone(), &table)?;
         system::register_system_functions(lua, ctx.clone(), &table)?;
+        events::register_event_functions(lua, ctx.clone(), &table)?;
+        monorepo::register_monorepo_functions(lua, ctx.clone(), &table)?;
 
         Ok(table)
     }
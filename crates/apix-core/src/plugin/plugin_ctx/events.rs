@@ -0,0 +1,34 @@
+use mlua::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::plugin::plugin_ctx::ctx::PluginCtx;
+use crate::plugin::types::ApiFunction;
+
+pub const API: &[ApiFunction] = &[ApiFunction {
+    name: "already_applied",
+    signature: "(action: string, args: { string }?) -> boolean",
+    doc: "Whether this plugin already applied `action` with `args` to the current target.",
+}];
+
+pub fn register_event_functions(
+    lua: &Lua,
+    ctx: Rc<RefCell<PluginCtx>>,
+    table: &LuaTable,
+) -> LuaResult<()> {
+    let applied_ctx = ctx.clone();
+    let applied_fn =
+        lua.create_function(move |_, (action, args): (String, Option<Vec<String>>)| {
+            let args = args.unwrap_or_default();
+
+            match &applied_ctx.borrow().history {
+                Some(history) => history
+                    .contains(&action, &args)
+                    .map_err(|e| LuaError::RuntimeError(e.to_string())),
+                None => Ok(false),
+            }
+        })?;
+    table.set("already_applied", applied_fn)?;
+
+    Ok(())
+}
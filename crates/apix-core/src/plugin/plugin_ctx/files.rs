@@ -3,6 +3,25 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::plugin::plugin_ctx::ctx::{PluginCtx, Proposal};
+use crate::plugin::types::ApiFunction;
+
+pub const API: &[ApiFunction] = &[
+    ApiFunction {
+        name: "create_file",
+        signature: "(path: string, content: string) -> ()",
+        doc: "Proposes creating `path`, relative to the monorepo root.",
+    },
+    ApiFunction {
+        name: "modify_file",
+        signature: "(path: string, content: string) -> ()",
+        doc: "Proposes replacing the content of `path`.",
+    },
+    ApiFunction {
+        name: "delete_file",
+        signature: "(path: string) -> ()",
+        doc: "Proposes deleting `path`.",
+    },
+];
 
 pub fn register_file_functions(
     lua: &Lua,
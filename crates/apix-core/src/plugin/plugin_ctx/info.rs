@@ -1,7 +1,11 @@
+use crate::plugin::args::{ArgKind, ArgSpec};
+
 #[derive(Debug)]
 pub struct PluginInfo {
     pub usage: Vec<String>,
     pub options: Vec<(String, String)>,
+    /// Typed arguments of `extend`, parsed by apix before the plugin sees them.
+    pub args: Vec<ArgSpec>,
 }
 
 impl PluginInfo {
@@ -10,19 +14,72 @@ impl PluginInfo {
         out.push_str(&format!("[{}: v{}]\n", name, version));
         out.push_str(&format!("{}\n\n", description));
 
+        let (positional, flags): (Vec<&ArgSpec>, Vec<&ArgSpec>) =
+            self.args.iter().partition(|arg| arg.positional);
+        let describe = |arg: &&ArgSpec| {
+            let mut desc = arg.description();
+            if arg.kind == ArgKind::Enum {
+                desc = format!("{} [possible values: {}]", desc, arg.values.join(", "))
+                    .trim()
+                    .to_string();
+            }
+            (arg.usage(), desc)
+        };
+        let arguments: Vec<(String, String)> = positional.iter().map(describe).collect();
+        let options: Vec<(String, String)> = flags
+            .iter()
+            .map(describe)
+            .chain(self.options.iter().cloned())
+            .collect();
+        let width = arguments
+            .iter()
+            .chain(&options)
+            .map(|(label, _)| label.len())
+            .max()
+            .unwrap_or(0)
+            .max(12);
+
         out.push_str("Usage:\n");
         for u in &self.usage {
             out.push_str(&format!("  {}\n", u));
         }
+        if self.usage.is_empty() && !self.args.is_empty() {
+            out.push_str(&format!("  {}\n", self.extend_usage(name)));
+        }
         out.push('\n');
 
-        if !self.options.is_empty() {
+        if !arguments.is_empty() {
+            out.push_str("Arguments:\n");
+            for (arg, desc) in &arguments {
+                out.push_str(&format!("  {:<width$} {}\n", arg, desc));
+            }
+            out.push('\n');
+        }
+
+        if !options.is_empty() {
             out.push_str("Options:\n");
-            for (flag, desc) in &self.options {
-                out.push_str(&format!("  {:<12} {}\n", flag, desc));
+            for (flag, desc) in &options {
+                out.push_str(&format!("  {:<width$} {}\n", flag, desc));
             }
         }
 
         out
     }
+
+    /// `apix plugin <name> extend [OPTIONS] <arg> [arg]` built from `args`.
+    fn extend_usage(&self, name: &str) -> String {
+        let mut usage = format!("apix plugin {} extend", name);
+        if self.args.iter().any(|arg| !arg.positional) {
+            usage.push_str(" [OPTIONS]");
+        }
+        for arg in self.args.iter().filter(|arg| arg.positional) {
+            if arg.required {
+                usage.push_str(&format!(" <{}>", arg.name));
+            } else {
+                usage.push_str(&format!(" [{}]", arg.name));
+            }
+        }
+
+        usage
+    }
 }
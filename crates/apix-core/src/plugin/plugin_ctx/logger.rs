@@ -5,10 +5,49 @@ use mlua::prelude::*;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::plugin::types::ApiFunction;
+
+pub const API: &[ApiFunction] = &[
+    ApiFunction {
+        name: "log",
+        signature: "(level: string, msg: string) -> ()",
+        doc: "Logs `msg` at `level`: error, warn, info, debug or trace.",
+    },
+    ApiFunction {
+        name: "error",
+        signature: "(msg: string) -> ()",
+        doc: "Logs `msg` at error level.",
+    },
+    ApiFunction {
+        name: "warn",
+        signature: "(msg: string) -> ()",
+        doc: "Logs `msg` at warn level.",
+    },
+    ApiFunction {
+        name: "info",
+        signature: "(msg: string) -> ()",
+        doc: "Logs `msg` at info level.",
+    },
+    ApiFunction {
+        name: "debug",
+        signature: "(msg: string) -> ()",
+        doc: "Logs `msg` at debug level.",
+    },
+    ApiFunction {
+        name: "trace",
+        signature: "(msg: string) -> ()",
+        doc: "Logs `msg` at trace level.",
+    },
+];
+
 #[derive(Debug)]
 pub struct PluginLogger {
     pub name: String,
     pub logs: Vec<String>,
+    /// `LEVEL message` without timestamp or colors, for assertions.
+    pub messages: Vec<String>,
+    /// Capture logs without printing them.
+    pub silent: bool,
 }
 
 impl PluginLogger {
@@ -16,6 +55,8 @@ impl PluginLogger {
         Self {
             name: name.to_string(),
             logs: Vec::new(),
+            messages: Vec::new(),
+            silent: false,
         }
     }
 }
@@ -61,8 +102,11 @@ pub fn register_logger_functions(
         let prefix = format!("[{}]", log_ctx.name);
         let formatted = format!("{} [{}] [{}] {}", prefix, ts, level_colored, msg_str);
 
-        println!("{}", formatted);
+        if !log_ctx.silent {
+            println!("{}", formatted);
+        }
         log_ctx.logs.push(formatted);
+        log_ctx.messages.push(format!("{} {}", level_enum, msg_str));
 
         Ok(())
     })?;
@@ -1,6 +1,8 @@
 pub mod ask;
 pub mod ctx;
+pub mod events;
 pub mod files;
 pub mod info;
 pub mod logger;
+pub mod monorepo;
 pub mod system;
@@ -0,0 +1,150 @@
+use mlua::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::monorepo::config::{MonorepoConfig, MonorepoTarget, UnitKind};
+use crate::plugin::plugin_ctx::ctx::{CreatedUnit, PluginCtx};
+use crate::plugin::types::ApiFunction;
+
+pub const API: &[ApiFunction] = &[ApiFunction {
+    name: "declare_created",
+    signature: "(unit: CreatedUnit) -> ()",
+    doc: "Registers a project or package in monorepo.toml once the plan is applied.",
+}];
+
+pub fn register_monorepo_functions(
+    lua: &Lua,
+    ctx: Rc<RefCell<PluginCtx>>,
+    table: &LuaTable,
+) -> LuaResult<()> {
+    let created_ctx = ctx.clone();
+    let created_fn = lua.create_function(move |_, unit: LuaTable| {
+        let name: String = unit.get("name")?;
+        let path: String = unit.get("path")?;
+        let language: String = unit.get("language")?;
+        let kind = match unit.get::<Option<String>>("kind")?.as_deref() {
+            None | Some("project") => UnitKind::Project,
+            Some("package") => UnitKind::Package,
+            Some(other) => {
+                return Err(LuaError::RuntimeError(format!(
+                    "Unknown kind '{}', expected 'project' or 'package'",
+                    other
+                )));
+            }
+        };
+
+        created_ctx.borrow_mut().created.push(CreatedUnit {
+            name,
+            kind,
+            path,
+            language,
+        });
+        Ok(())
+    })?;
+    table.set("declare_created", created_fn)?;
+
+    Ok(())
+}
+
+/// Builds the read-only `ctx.monorepo` table from `monorepo.toml`. `target` is
+/// the project or package the command was scoped to, if any.
+pub fn create_monorepo_table(
+    lua: &Lua,
+    config: &MonorepoConfig,
+    target: Option<&MonorepoTarget>,
+) -> LuaResult<LuaTable> {
+    let table = lua.create_table()?;
+
+    let repo = lua.create_table()?;
+    repo.set("name", config.repo.name.as_str())?;
+    repo.set("version", config.repo.version.as_str())?;
+    repo.set("template", config.repo.template.as_str())?;
+    table.set("repo", repo)?;
+
+    let projects = lua.create_table()?;
+    for (name, project) in &config.projects {
+        let unit = create_unit_table(
+            lua,
+            name,
+            UnitKind::Project.as_str(),
+            &project.path,
+            &project.language,
+            &project.permissions,
+            project.plugin.as_deref(),
+        )?;
+        projects.set(name.as_str(), unit)?;
+    }
+    table.set("projects", &projects)?;
+
+    let packages = lua.create_table()?;
+    for (name, package) in &config.packages {
+        let unit = create_unit_table(
+            lua,
+            name,
+            UnitKind::Package.as_str(),
+            &package.path,
+            &package.language,
+            &package.permissions,
+            package.plugin.as_deref(),
+        )?;
+        packages.set(name.as_str(), unit)?;
+    }
+
+    if let Some(target) = target {
+        let units = match target.kind {
+            UnitKind::Project => &projects,
+            UnitKind::Package => &packages,
+        };
+        table.set("target", units.get::<LuaTable>(target.name.as_str())?)?;
+    }
+
+    table.set("packages", packages)?;
+
+    let plugins = lua.create_table()?;
+    for (name, meta) in &config.plugins {
+        plugins.set(name.as_str(), meta.version())?;
+    }
+    table.set("plugins", plugins)?;
+
+    freeze(&table)?;
+    Ok(table)
+}
+
+fn create_unit_table(
+    lua: &Lua,
+    name: &str,
+    kind: &str,
+    path: &str,
+    language: &str,
+    permissions: &[(String, Vec<String>)],
+    plugin: Option<&str>,
+) -> LuaResult<LuaTable> {
+    let unit = lua.create_table()?;
+    unit.set("name", name)?;
+    unit.set("kind", kind)?;
+    unit.set("path", path)?;
+    unit.set("language", language)?;
+    unit.set("plugin", plugin)?;
+
+    let perms = lua.create_table()?;
+    for (scope, targets) in permissions {
+        perms.push(lua.create_sequence_from([
+            LuaValue::String(lua.create_string(scope)?),
+            LuaValue::Table(lua.create_sequence_from(targets.iter().map(String::as_str))?),
+        ])?)?;
+    }
+    unit.set("permissions", perms)?;
+
+    Ok(unit)
+}
+
+fn freeze(table: &LuaTable) -> LuaResult<()> {
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        if let (_, LuaValue::Table(inner)) = pair? {
+            freeze(&inner)?;
+        }
+    }
+    table.set_readonly(true);
+
+    Ok(())
+}
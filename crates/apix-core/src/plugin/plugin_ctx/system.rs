@@ -1,9 +1,29 @@
 use mlua::{Function as LuaFunction, Lua, Result as LuaResult, Table as LuaTable};
-#[cfg(unix)]
-use std::process::Command;
 use std::{cell::RefCell, rc::Rc};
 
-use crate::plugin::plugin_ctx::ctx::{PluginCtx, Proposal};
+use crate::plugin::{
+    plugin_ctx::ctx::{PluginCtx, Proposal},
+    tools::{probe_version, tool_exists},
+    types::ApiFunction,
+};
+
+pub const API: &[ApiFunction] = &[
+    ApiFunction {
+        name: "system",
+        signature: "(command: string, args: { string }?) -> ()",
+        doc: "Proposes running `command` in the target's folder, or the monorepo root without a target.",
+    },
+    ApiFunction {
+        name: "system_exists",
+        signature: "(command: string) -> boolean",
+        doc: "Whether `command` is on PATH.",
+    },
+    ApiFunction {
+        name: "system_version",
+        signature: "(command: string, flag: string?, regex: string?) -> SystemVersion",
+        doc: "Runs `command flag` (default `--version`) and parses the version it prints.",
+    },
+];
 
 pub fn register_system_functions(
     lua: &Lua,
@@ -23,6 +43,7 @@ pub fn register_system_functions(
                 .push(Proposal::SystemCommand {
                     command: command.clone(),
                     args: args.clone(),
+                    dir: None,
                 });
 
             let log_fn: LuaFunction = table_clone.get("info")?;
@@ -33,36 +54,23 @@ pub fn register_system_functions(
 
     table.set("system", sys_fn)?;
 
-    let exists_fn = lua.create_function(|_, command: String| {
-        #[cfg(unix)]
-        let status = Command::new("which").arg(&command).status();
-
-        #[cfg(windows)]
-        let status = Command::new("where").arg(&command).status();
-
-        let exists = status.map(|s| s.success()).unwrap_or(false);
-
-        Ok(exists)
-    })?;
+    let exists_fn = lua.create_function(|_, command: String| Ok(tool_exists(&command)))?;
     table.set("system_exists", exists_fn)?;
 
-    let version_fn = lua.create_function(|_, (command, flag): (String, Option<String>)| {
-        let flag = flag.unwrap_or_else(|| "--version".to_string());
+    let version_fn = lua.create_function(
+        |lua, (command, flag, regex): (String, Option<String>, Option<String>)| {
+            let flag = flag.unwrap_or_else(|| "--version".to_string());
+            let probed = probe_version(&command, &flag, regex.as_deref())
+                .map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
 
-        let output = Command::new(&command).arg(&flag).output();
+            let result = lua.create_table()?;
+            result.set("ok", probed.ok)?;
+            result.set("version", probed.version.map(|v| v.to_string()))?;
+            result.set("raw", probed.raw)?;
 
-        match output {
-            Ok(out) if out.status.success() => {
-                let stdout = String::from_utf8_lossy(&out.stdout).trim().to_string();
-                Ok(stdout)
-            }
-            Ok(out) => {
-                let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-                Ok(format!("(error) {}", stderr))
-            }
-            Err(e) => Ok(format!("(failed to run '{} {}': {})", command, flag, e)),
-        }
-    })?;
+            Ok(result)
+        },
+    )?;
     table.set("system_version", version_fn)?;
 
     Ok(())
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    plugin::{
+        config::read_plugin_config,
+        validate::{format_diagnostics, has_errors, validate_working_copy},
+    },
+    template::render::render_str,
+};
+
+const PLUGIN_TOML: &str = include_str!("../../plugin-template/plugin.toml");
+const ENTRY_LUA: &str = include_str!("../../plugin-template/entry.lua");
+const ENTRY_LUAU: &str = include_str!("../../plugin-template/entry.luau");
+const README: &str = include_str!("../../plugin-template/README.md");
+const FIXTURE_MONOREPO: &str = include_str!("../../plugin-template/tests/fixture/monorepo.toml");
+const CREATE_CASE: &str = include_str!("../../plugin-template/tests/create.toml");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginLang {
+    Lua,
+    Luau,
+}
+
+impl PluginLang {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "lua" => Ok(PluginLang::Lua),
+            "luau" => Ok(PluginLang::Luau),
+            other => Err(format!("expected 'lua' or 'luau', got '{}'", other)),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PluginLang::Lua => "lua",
+            PluginLang::Luau => "luau",
+        }
+    }
+}
+
+/// Creates a new plugin working copy in `dst_dir`, which must not exist yet.
+pub fn scaffold_plugin(dst_dir: &Path, name: &str, lang: PluginLang) -> Result<(), Box<dyn Error>> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid plugin name '{}', use letters, digits, '-' and '_'",
+            name
+        )
+        .into());
+    }
+
+    if dst_dir.exists() {
+        return Err(format!("'{}' already exists", dst_dir.display()).into());
+    }
+
+    let entry = format!("{}.{}", name, lang.extension());
+    let vars = HashMap::from([
+        ("name".to_string(), name.to_string()),
+        ("entry".to_string(), entry.clone()),
+    ]);
+
+    let entry_source = match lang {
+        PluginLang::Lua => ENTRY_LUA,
+        PluginLang::Luau => ENTRY_LUAU,
+    };
+
+    let files = [
+        ("plugin.toml", PLUGIN_TOML),
+        (entry.as_str(), entry_source),
+        ("README.md", README),
+        ("data/.gitkeep", ""),
+        ("tests/fixture/monorepo.toml", FIXTURE_MONOREPO),
+        ("tests/create.toml", CREATE_CASE),
+    ];
+
+    let result = files.iter().try_for_each(|(path, content)| {
+        let path = dst_dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, render_str(content, &vars))
+    });
+
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(dst_dir);
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+/// Symlinks a plugin working copy to `<plugins_dir>/<name>/<version>` so changes
+/// are picked up without reinstalling. Returns the link path.
+pub fn link_plugin(src_dir: &Path, plugins_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let src_dir = fs::canonicalize(src_dir)
+        .map_err(|e| format!("Cannot resolve '{}': {}", src_dir.display(), e))?;
+
+    // The name and version become path components of the link.
+    let diagnostics = validate_working_copy(&src_dir);
+    if has_errors(&diagnostics) {
+        let name = src_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        return Err(format_diagnostics(&name, &diagnostics).into());
+    }
+    let config = read_plugin_config(&src_dir)?;
+
+    let link = plugins_dir.join(&config.name).join(&config.version);
+
+    if let Ok(metadata) = fs::symlink_metadata(&link) {
+        if !metadata.file_type().is_symlink() {
+            return Err(format!(
+                "'{}' {} is already installed at '{}', remove it before linking",
+                config.name,
+                config.version,
+                link.display()
+            )
+            .into());
+        }
+        fs::remove_file(&link)?;
+    }
+
+    fs::create_dir_all(plugins_dir.join(&config.name))?;
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&src_dir, &link)?;
+
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(&src_dir, &link)?;
+
+    Ok(link)
+}
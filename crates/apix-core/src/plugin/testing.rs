@@ -0,0 +1,299 @@
+use serde::Deserialize;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    monorepo::config::get_monorepo_config,
+    plugin::{
+        args::{parse_args, validate_args},
+        config::read_plugin_config,
+        plan::Plan,
+        plugin_ctx::ctx::{CreatedUnit, PluginCtx, Proposal},
+        scaffold::link_plugin,
+        utils::load_plugin_with_ctx,
+    },
+};
+
+/// Folder inside a plugin holding `<case>.toml` files and the fixture monorepo.
+pub const TESTS_DIR: &str = "tests";
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// One `tests/<case>.toml` file.
+#[derive(Debug, Deserialize)]
+pub struct TestCase {
+    #[serde(skip)]
+    pub name: String,
+    /// `create`, `extend` or `migrate`.
+    pub action: String,
+    /// Project name for `create`, arguments for `extend`, from-version for `migrate`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Answers returned by `ctx.ask`, in order.
+    #[serde(default)]
+    pub answers: Vec<String>,
+    /// Monorepo folder relative to `tests/`.
+    #[serde(default = "default_fixture")]
+    pub fixture: String,
+    pub project: Option<String>,
+    pub package: Option<String>,
+}
+
+fn default_fixture() -> String {
+    "fixture".to_string()
+}
+
+/// Everything a plugin call produced, without touching the fixture.
+#[derive(Debug)]
+pub struct TestOutcome {
+    pub exit_code: i32,
+    pub logs: Vec<String>,
+    pub created: Vec<CreatedUnit>,
+    pub proposals: Vec<Proposal>,
+    /// Error from validating the proposals against the fixture, if any.
+    pub plan_error: Option<String>,
+}
+
+pub enum SnapshotResult {
+    Matched,
+    /// The snapshot was written or rewritten in update mode.
+    Updated,
+    Missing,
+    Mismatch {
+        expected: String,
+        actual: String,
+    },
+}
+
+impl TestCase {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let mut case: TestCase =
+            toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        case.name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Ok(case)
+    }
+}
+
+/// All test cases of the plugin in `plugin_dir`, sorted by name.
+pub fn discover_cases(plugin_dir: &Path) -> Result<Vec<TestCase>, Box<dyn Error>> {
+    let tests_dir = plugin_dir.join(TESTS_DIR);
+    if !tests_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&tests_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    paths.iter().map(|path| TestCase::load(path)).collect()
+}
+
+/// Loads the plugin working copy in `plugin_dir` against the case's fixture
+/// monorepo and calls the action with scripted answers. Dependencies resolve
+/// against the plugins installed in `installed_dir`.
+pub fn run_plugin_test(
+    plugin_dir: &Path,
+    installed_dir: &Path,
+    case: &TestCase,
+) -> Result<TestOutcome, Box<dyn Error>> {
+    let config = read_plugin_config(plugin_dir)?;
+    let fixture = fs::canonicalize(plugin_dir.join(TESTS_DIR).join(&case.fixture))
+        .map_err(|e| format!("Fixture '{}' not found: {}", case.fixture, e))?;
+
+    // The loader expects the <plugins>/<name>/<version> layout.
+    let plugins_dir = tempfile::tempdir()?;
+    link_installed(installed_dir, plugins_dir.path(), &config.name)?;
+    link_plugin(plugin_dir, plugins_dir.path())?;
+
+    let target = match (&case.project, &case.package) {
+        (None, None) => None,
+        (project, package) => {
+            let monorepo = get_monorepo_config(&fixture)?;
+            let target = match (project, package) {
+                (Some(name), _) => monorepo.project_target(name),
+                (_, Some(name)) => monorepo.package_target(name),
+                _ => None,
+            };
+            Some(target.ok_or("Test target is not registered in the fixture monorepo.toml")?)
+        }
+    };
+
+    let ctx = Rc::new(RefCell::new(PluginCtx::new(&config.name)));
+    {
+        let mut ctx = ctx.borrow_mut();
+        ctx.target = target;
+        ctx.answers = Some(case.answers.iter().cloned().collect::<VecDeque<_>>());
+        ctx.logger.borrow_mut().silent = true;
+    }
+
+    let abi = load_plugin_with_ctx(
+        &config.name,
+        &config.version,
+        &fixture,
+        plugins_dir.path(),
+        ctx.clone(),
+    )?;
+
+    let exit_code = match case.action.as_str() {
+        "create" => abi.create(case.args.first().cloned().unwrap_or_default())?,
+        "extend" => {
+            let declared = abi.extend_args(&config)?;
+            if declared.is_empty() {
+                abi.extend(case.args.clone())?
+            } else {
+                validate_args(&declared)?;
+                let values = parse_args(
+                    format!("apix plugin {} extend", config.name),
+                    &declared,
+                    &case.args,
+                )
+                .map_err(|e| e.render().to_string())?;
+                abi.extend_with(&values)?
+            }
+        }
+        "migrate" => abi.migrate(case.args.first().cloned().unwrap_or_default())?,
+        other => return Err(format!("Unknown test action '{}'", other).into()),
+    };
+
+    let mut ctx = ctx.borrow_mut();
+    let logs = ctx.logger.borrow().messages.clone();
+    let plan = Plan::new(ctx.proposals.drain(..).collect());
+    let plan_error = plan.validate(&fixture).err().map(|e| e.to_string());
+
+    Ok(TestOutcome {
+        exit_code,
+        logs,
+        created: ctx.created.clone(),
+        proposals: plan.proposals,
+        plan_error,
+    })
+}
+
+/// Symlinks every installed version except those of `skip` into `plugins_dir`,
+/// so path dependencies get installed into the temporary folder only.
+fn link_installed(installed_dir: &Path, plugins_dir: &Path, skip: &str) -> std::io::Result<()> {
+    if !installed_dir.is_dir() {
+        return Ok(());
+    }
+
+    for plugin in fs::read_dir(installed_dir)? {
+        let plugin = plugin?;
+        if plugin.file_name() == skip || !plugin.path().is_dir() {
+            continue;
+        }
+
+        let dst = plugins_dir.join(plugin.file_name());
+        fs::create_dir_all(&dst)?;
+        for version in fs::read_dir(plugin.path())? {
+            let version = version?;
+
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(version.path(), dst.join(version.file_name()))?;
+
+            #[cfg(windows)]
+            std::os::windows::fs::symlink_dir(version.path(), dst.join(version.file_name()))?;
+        }
+    }
+
+    Ok(())
+}
+
+impl TestOutcome {
+    /// Stable text form of the outcome stored in snapshot files.
+    pub fn snapshot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "exit: {}", self.exit_code);
+
+        if let Some(error) = &self.plan_error {
+            let _ = writeln!(out, "invalid plan: {}", error);
+        }
+
+        out.push_str("\nlogs:\n");
+        for log in &self.logs {
+            let _ = writeln!(out, "  {}", log);
+        }
+
+        out.push_str("\ncreated:\n");
+        for unit in &self.created {
+            let _ = writeln!(
+                out,
+                "  {} {} at {} ({})",
+                unit.kind.as_str(),
+                unit.name,
+                unit.path,
+                unit.language
+            );
+        }
+
+        out.push_str("\nproposals:\n");
+        for proposal in &self.proposals {
+            match proposal {
+                Proposal::CreateFile { path, content } => {
+                    let _ = writeln!(out, "  A  {}", path);
+                    write_content(&mut out, content);
+                }
+                Proposal::ModifyFile { path, content } => {
+                    let _ = writeln!(out, "  M  {}", path);
+                    write_content(&mut out, content);
+                }
+                Proposal::DeleteFile { path } => {
+                    let _ = writeln!(out, "  D  {}", path);
+                }
+                Proposal::SystemCommand { command, args, .. } => {
+                    let _ = writeln!(out, "  $  {} {}", command, args.join(" "));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn write_content(out: &mut String, content: &str) {
+    for line in content.lines() {
+        let _ = writeln!(out, "    | {}", line);
+    }
+}
+
+/// Compares the outcome with `tests/snapshots/<case>.snap`, or rewrites it when `update` is set.
+pub fn check_snapshot(
+    plugin_dir: &Path,
+    case: &TestCase,
+    outcome: &TestOutcome,
+    update: bool,
+) -> Result<SnapshotResult, Box<dyn Error>> {
+    let snapshot_path = plugin_dir
+        .join(TESTS_DIR)
+        .join(SNAPSHOTS_DIR)
+        .join(format!("{}.snap", case.name));
+    let actual = outcome.snapshot();
+
+    let expected = fs::read_to_string(&snapshot_path).ok();
+    if expected.as_deref() == Some(actual.as_str()) {
+        return Ok(SnapshotResult::Matched);
+    }
+
+    if update {
+        fs::create_dir_all(snapshot_path.parent().unwrap())?;
+        fs::write(&snapshot_path, &actual)?;
+        return Ok(SnapshotResult::Updated);
+    }
+
+    Ok(match expected {
+        Some(expected) => SnapshotResult::Mismatch { expected, actual },
+        None => SnapshotResult::Missing,
+    })
+}
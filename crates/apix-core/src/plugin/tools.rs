@@ -0,0 +1,159 @@
+use regex::Regex;
+use semver::{Version, VersionReq};
+use std::{error::Error, process::Command};
+
+use crate::plugin::config::{PluginConfig, ToolRequirement};
+
+/// Matches the first `major.minor[.patch]` looking token in a version output.
+const DEFAULT_VERSION_REGEX: &str = r"(\d+\.\d+(?:\.\d+)?(?:-[0-9A-Za-z.-]+)?)";
+
+/// Result of running `<tool> --version`.
+#[derive(Debug, Clone)]
+pub struct ToolVersion {
+    /// The command ran and exited successfully.
+    pub ok: bool,
+    pub version: Option<Version>,
+    /// Trimmed stdout, or stderr when stdout is empty or the command failed.
+    pub raw: String,
+}
+
+pub fn tool_exists(command: &str) -> bool {
+    #[cfg(unix)]
+    let status = Command::new("which").arg(command).output();
+
+    #[cfg(windows)]
+    let status = Command::new("where").arg(command).output();
+
+    status.map(|s| s.status.success()).unwrap_or(false)
+}
+
+/// Runs `command flag` and extracts a semver version from its output with
+/// `regex`, or the first version-looking token if no regex is given.
+pub fn probe_version(
+    command: &str,
+    flag: &str,
+    regex: Option<&str>,
+) -> Result<ToolVersion, Box<dyn Error>> {
+    let regex = Regex::new(regex.unwrap_or(DEFAULT_VERSION_REGEX))
+        .map_err(|e| format!("Invalid version regex for '{}': {}", command, e))?;
+
+    let output = match Command::new(command).arg(flag).output() {
+        Ok(output) => output,
+        Err(e) => {
+            return Ok(ToolVersion {
+                ok: false,
+                version: None,
+                raw: format!("failed to run '{} {}': {}", command, flag, e),
+            });
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let raw = if output.status.success() && !stdout.is_empty() {
+        stdout
+    } else {
+        stderr
+    };
+
+    let version = regex
+        .captures(&raw)
+        .and_then(|c| c.get(1).or_else(|| c.get(0)))
+        .and_then(|m| parse_lenient(m.as_str()));
+
+    Ok(ToolVersion {
+        ok: output.status.success(),
+        version,
+        raw,
+    })
+}
+
+/// Checks one requirement, returning the detected version if it matches.
+pub fn check_tool(name: &str, requirement: &ToolRequirement) -> Result<Version, String> {
+    let req = VersionReq::parse(requirement.version()).map_err(|e| {
+        format!(
+            "'{}' has an invalid version constraint '{}': {}",
+            name,
+            requirement.version(),
+            e
+        )
+    })?;
+
+    if !tool_exists(name) {
+        return Err(format!("'{}' is not installed or not on PATH", name));
+    }
+
+    let probed =
+        probe_version(name, requirement.flag(), requirement.regex()).map_err(|e| e.to_string())?;
+
+    let Some(version) = probed.version else {
+        return Err(format!(
+            "could not read the version of '{}' from '{} {}': {}",
+            name,
+            name,
+            requirement.flag(),
+            probed.raw.lines().next().unwrap_or_default()
+        ));
+    };
+
+    // Requirements without a pre-release never match pre-release versions,
+    // so a `1.84.0-nightly` toolchain is checked as `1.84.0`.
+    let release = Version::new(version.major, version.minor, version.patch);
+    if !req.matches(&release) {
+        return Err(format!(
+            "'{}' {} does not satisfy '{}'",
+            name,
+            version,
+            requirement.version()
+        ));
+    }
+
+    Ok(version)
+}
+
+/// Checks every tool in `[requires.tools]` and reports all failures at once.
+pub fn check_tool_requirements(config: &PluginConfig) -> Result<(), Box<dyn Error>> {
+    let failures: Vec<String> = config
+        .requires
+        .tools
+        .iter()
+        .filter_map(|(name, requirement)| check_tool(name, requirement).err())
+        .collect();
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Plugin '{}' requirements not met:\n  - {}",
+        config.name,
+        failures.join("\n  - ")
+    )
+    .into())
+}
+
+/// Parses versions like `1.80` or `1.80.0-nightly` that tools commonly print.
+fn parse_lenient(version: &str) -> Option<Version> {
+    if let Ok(version) = Version::parse(version) {
+        return Some(version);
+    }
+
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let mut parts = core.split('.').map(|p| p.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+
+    let normalized = match pre {
+        Some(pre) => format!("{}.{}.{}-{}", major, minor, patch, pre),
+        None => format!("{}.{}.{}", major, minor, patch),
+    };
+
+    Version::parse(&normalized)
+        .ok()
+        .or_else(|| Some(Version::new(major, minor, patch)))
+}
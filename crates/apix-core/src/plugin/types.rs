@@ -0,0 +1,287 @@
+use mlua::{Lua, Table as LuaTable};
+use std::{
+    cell::RefCell, collections::BTreeSet, error::Error, fmt::Write as _, path::PathBuf, rc::Rc,
+};
+
+use crate::plugin::{
+    file_tree::{self, LuaDir, LuaFile},
+    plugin_ctx::{ask, ctx::PluginCtx, events, files, logger, monorepo, system},
+};
+
+/// File name editors pick up as luau-lsp definitions.
+pub const DEFINITIONS_FILE: &str = "apix.d.luau";
+
+/// Luau declaration of one function or method registered from Rust.
+pub struct ApiFunction {
+    pub name: &'static str,
+    /// Function type without the name, e.g. `(path: string) -> ()`.
+    pub signature: &'static str,
+    pub doc: &'static str,
+}
+
+/// Table shapes returned or accepted by the ctx functions.
+const TYPE_ALIASES: &str = "\
+type SystemVersion = {
+\tok: boolean,
+\tversion: string?,
+\traw: string,
+}
+
+type CreatedUnit = {
+\tname: string,
+\tkind: (\"project\" | \"package\")?,
+\tpath: string,
+\tlanguage: string,
+}
+
+type MonorepoUnit = {
+\tname: string,
+\tkind: \"project\" | \"package\",
+\tpath: string,
+\tlanguage: string,
+\tplugin: string?,
+\tpermissions: { { any } },
+}
+
+type Monorepo = {
+\trepo: { name: string, version: string, template: string },
+\tprojects: { [string]: MonorepoUnit },
+\tpackages: { [string]: MonorepoUnit },
+\tplugins: { [string]: string },
+\ttarget: MonorepoUnit?,
+}
+
+type HookEvent = {
+\tname: string,
+\tsource: string,
+\tunit: CreatedUnit?,
+\tplugin: { name: string, version: string }?,
+\tfiles: { string },
+}
+";
+
+pub(crate) fn ctx_api() -> impl Iterator<Item = &'static ApiFunction> {
+    [
+        logger::API,
+        ask::API,
+        files::API,
+        system::API,
+        events::API,
+        monorepo::API,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Renders the luau-lsp definitions for `ctx`, `monorepo_root_dir` and
+/// `plugin_data_dir`. Fails if the declarations and the functions actually
+/// registered have drifted apart.
+pub fn generate_definitions() -> Result<String, Box<dyn Error>> {
+    let lua = Lua::new();
+    let ctx = PluginCtx::register(&lua, Rc::new(RefCell::new(PluginCtx::new("types"))))?;
+
+    check_declared("ctx", &table_keys(&ctx)?, ctx_api())?;
+
+    let file = lua.create_userdata(LuaFile {
+        path: PathBuf::new(),
+        content: None,
+    })?;
+    let dir = lua.create_userdata(LuaDir {
+        path: PathBuf::new(),
+        entries: Vec::new(),
+    })?;
+    check_declared("LuaFile", &method_names(&file)?, file_tree::FILE_API)?;
+    check_declared("LuaDir", &method_names(&dir)?, file_tree::DIR_API)?;
+
+    let mut out = String::from("-- Generated by `apix plugin-dev types`, do not edit.\n\n");
+    out.push_str(TYPE_ALIASES);
+
+    write_class(&mut out, "LuaFile", file_tree::FILE_API, true);
+    write_class(&mut out, "LuaDir", file_tree::DIR_API, true);
+
+    out.push_str("\ndeclare class Ctx\n");
+    out.push_str("\t--- `monorepo.toml` as a read-only table, nil outside a monorepo.\n");
+    out.push_str("\tmonorepo: Monorepo?\n");
+    write_members(&mut out, ctx_api(), false);
+    out.push_str("end\n");
+
+    out.push_str("\ndeclare ctx: Ctx\n");
+    out.push_str("--- Root of the monorepo the plugin runs in.\n");
+    out.push_str("declare monorepo_root_dir: LuaDir\n");
+    out.push_str("--- The plugin's `data/` folder.\n");
+    out.push_str("declare plugin_data_dir: LuaDir\n");
+
+    Ok(out)
+}
+
+fn write_class(out: &mut String, name: &str, api: &[ApiFunction], methods: bool) {
+    let _ = writeln!(out, "\ndeclare class {}", name);
+    write_members(out, api.iter(), methods);
+    out.push_str("end\n");
+}
+
+/// Methods take `self` and are called with `:`, plain fields with `.`.
+fn write_members<'a>(out: &mut String, api: impl Iterator<Item = &'a ApiFunction>, methods: bool) {
+    for function in api {
+        let _ = writeln!(out, "\t--- {}", function.doc);
+        if methods {
+            let (params, ret) = function
+                .signature
+                .split_once(" -> ")
+                .unwrap_or((function.signature, "()"));
+            let params = params.trim_start_matches('(').trim_end_matches(')');
+            let params = if params.is_empty() {
+                "self".to_string()
+            } else {
+                format!("self, {}", params)
+            };
+            let _ = writeln!(out, "\tfunction {}({}): {}", function.name, params, ret);
+        } else {
+            let _ = writeln!(out, "\t{}: {}", function.name, function.signature);
+        }
+    }
+}
+
+fn table_keys(table: &LuaTable) -> mlua::Result<BTreeSet<String>> {
+    table
+        .pairs::<String, mlua::Value>()
+        .map(|pair| pair.map(|(key, _)| key))
+        .collect()
+}
+
+fn method_names(userdata: &mlua::AnyUserData) -> mlua::Result<BTreeSet<String>> {
+    table_keys(&userdata.metatable()?.get::<LuaTable>("__index")?)
+}
+
+fn check_declared<'a>(
+    owner: &str,
+    registered: &BTreeSet<String>,
+    api: impl IntoIterator<Item = &'a ApiFunction>,
+) -> Result<(), Box<dyn Error>> {
+    let declared: BTreeSet<String> = api.into_iter().map(|f| f.name.to_string()).collect();
+
+    let undeclared: Vec<_> = registered.difference(&declared).cloned().collect();
+    let stale: Vec<_> = declared.difference(registered).cloned().collect();
+
+    if undeclared.is_empty() && stale.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = format!("{} type declarations are out of date:", owner);
+    for name in undeclared {
+        let _ = write!(
+            message,
+            "\n  - '{}' is registered but has no declaration",
+            name
+        );
+    }
+    for name in stale {
+        let _ = write!(message, "\n  - '{}' is declared but not registered", name);
+    }
+
+    Err(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_api() -> impl Iterator<Item = &'static ApiFunction> {
+        ctx_api()
+            .chain(file_tree::FILE_API)
+            .chain(file_tree::DIR_API)
+    }
+
+    #[test]
+    fn generate_definitions_declares_every_function() {
+        let definitions = generate_definitions().unwrap();
+
+        for declaration in [
+            "declare class LuaFile\n",
+            "declare class LuaDir\n",
+            "declare class Ctx\n",
+            "declare ctx: Ctx\n",
+            "declare monorepo_root_dir: LuaDir\n",
+            "declare plugin_data_dir: LuaDir\n",
+        ] {
+            assert!(definitions.contains(declaration), "{}", declaration);
+        }
+        for function in ctx_api() {
+            let line = format!("\t{}: {}\n", function.name, function.signature);
+            assert!(definitions.contains(&line), "{}", line);
+        }
+        for function in file_tree::FILE_API.iter().chain(file_tree::DIR_API) {
+            let line = format!("\tfunction {}(self", function.name);
+            assert!(definitions.contains(&line), "{}", line);
+        }
+    }
+
+    #[test]
+    fn signatures_are_function_types_over_declared_types() {
+        let mut known: BTreeSet<&str> = TYPE_ALIASES
+            .lines()
+            .filter_map(|line| line.strip_prefix("type "))
+            .filter_map(|line| line.split_whitespace().next())
+            .collect();
+        known.extend(["LuaFile", "LuaDir"]);
+        let type_name = regex::Regex::new(r"\b[A-Z][A-Za-z]*\b").unwrap();
+
+        for function in all_api() {
+            let signature = function.signature;
+            let (params, ret) = signature
+                .split_once(" -> ")
+                .unwrap_or_else(|| panic!("{}: no return type in '{}'", function.name, signature));
+            let params = params
+                .strip_prefix('(')
+                .and_then(|p| p.strip_suffix(')'))
+                .unwrap_or_else(|| panic!("{}: unbalanced '{}'", function.name, signature));
+            assert!(!ret.is_empty(), "{}", function.name);
+
+            let mut depth = 0i32;
+            for c in signature.chars() {
+                match c {
+                    '(' | '{' => depth += 1,
+                    ')' | '}' => depth -= 1,
+                    _ => {}
+                }
+                assert!(depth >= 0, "{}: unbalanced '{}'", function.name, signature);
+            }
+            assert_eq!(depth, 0, "{}: unbalanced '{}'", function.name, signature);
+
+            for param in params.split(", ").filter(|p| !p.is_empty()) {
+                let (name, ty) = param
+                    .split_once(": ")
+                    .unwrap_or_else(|| panic!("{}: untyped parameter '{}'", function.name, param));
+                assert!(
+                    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') && !ty.is_empty(),
+                    "{}: malformed parameter '{}'",
+                    function.name,
+                    param
+                );
+            }
+
+            for ty in type_name.find_iter(signature) {
+                assert!(
+                    known.contains(ty.as_str()),
+                    "{}: unknown type '{}'",
+                    function.name,
+                    ty.as_str()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn check_declared_reports_drift() {
+        let registered: BTreeSet<String> = ["log".to_string(), "ask".to_string()].into();
+        let api = [ApiFunction {
+            name: "log",
+            signature: "(msg: string) -> ()",
+            doc: "",
+        }];
+        assert!(check_declared("ctx", &registered, &api).is_err());
+
+        let registered: BTreeSet<String> = ["log".to_string()].into();
+        assert!(check_declared("ctx", &registered, &api).is_ok());
+    }
+}
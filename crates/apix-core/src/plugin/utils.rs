@@ -1,24 +1,130 @@
+use log::warn;
 use mlua::Result as LuaResult;
-use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use crate::plugin::{instance::PluginInstance, loader::LuaPlugin, plugin_ctx::ctx::PluginCtx};
+use crate::{
+    monorepo::config::MonorepoTarget,
+    plugin::{
+        check::{check_plugin, is_strict},
+        config::{PluginKind, read_plugin_config},
+        dependencies::{Lockfile, locked_versions, resolve_dependencies},
+        instance::PluginInstance,
+        loader::{LuaPlugin, entry_path},
+        plugin_ctx::ctx::PluginCtx,
+        tools::check_tool_requirements,
+        validate::{format_diagnostics, has_errors, validate_entry_points, validate_manifest},
+    },
+};
 
 pub fn load_plugin(
     name: &str,
     plugin_version: &str,
     monorepo_root: &PathBuf,
-    plugins_dir: &PathBuf,
+    plugins_dir: &Path,
+    target: Option<MonorepoTarget>,
 ) -> LuaResult<(PluginInstance, Rc<RefCell<PluginCtx>>)> {
     let ctx = Rc::new(RefCell::new(PluginCtx::new(name)));
+    ctx.borrow_mut().target = target;
 
-    let lua_plugin = LuaPlugin::load(
+    let abi = load_plugin_with_ctx(
         name,
-        &plugins_dir,
         plugin_version,
         monorepo_root,
+        plugins_dir,
         ctx.clone(),
     )?;
-    let abi = PluginInstance::new(lua_plugin);
 
     Ok((abi, ctx))
 }
+
+/// Like [`load_plugin`], with a context prepared by the caller, e.g. with
+/// scripted answers or silenced logs.
+pub fn load_plugin_with_ctx(
+    name: &str,
+    plugin_version: &str,
+    monorepo_root: &PathBuf,
+    plugins_dir: &Path,
+    ctx: Rc<RefCell<PluginCtx>>,
+) -> LuaResult<PluginInstance> {
+    let version_dir = plugins_dir.join(name).join(plugin_version);
+
+    let mut diagnostics = validate_manifest(&version_dir);
+    if has_errors(&diagnostics) {
+        return Err(mlua::Error::RuntimeError(format_diagnostics(
+            name,
+            &diagnostics,
+        )));
+    }
+
+    let plugin_config =
+        read_plugin_config(&version_dir).map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
+    if plugin_config.kind == PluginKind::Library {
+        return Err(mlua::Error::RuntimeError(format!(
+            "'{}' is a library, it can only be required by other plugins",
+            name
+        )));
+    }
+
+    let lock =
+        Lockfile::load(monorepo_root).map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
+    let dependencies = resolve_dependencies(
+        plugins_dir,
+        &version_dir,
+        &plugin_config,
+        lock.plugins.get(name),
+    )
+    .map_err(|e| mlua::Error::RuntimeError(format!("Plugin '{}' dependencies: {}", name, e)))?;
+
+    check_tool_requirements(&plugin_config)
+        .map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
+    for dependency in dependencies.values() {
+        check_tool_requirements(&dependency.config)
+            .map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
+    }
+
+    let entry = entry_path(&version_dir, name)?;
+    if is_strict(&fs::read_to_string(&entry)?) {
+        let report =
+            check_plugin(&version_dir).map_err(|e| mlua::Error::RuntimeError(e.to_string()))?;
+        diagnostics.extend(report.diagnostics);
+        if has_errors(&diagnostics) {
+            return Err(mlua::Error::RuntimeError(format!(
+                "{}\n  ({})",
+                format_diagnostics(name, &diagnostics),
+                report.analyzer.scope()
+            )));
+        }
+    }
+
+    let lua_plugin = LuaPlugin::load(
+        name,
+        plugins_dir,
+        plugin_version,
+        monorepo_root,
+        ctx,
+        &plugin_config,
+        &dependencies,
+    )?;
+
+    diagnostics.extend(validate_entry_points(&lua_plugin.lua, &plugin_config));
+    if has_errors(&diagnostics) {
+        return Err(mlua::Error::RuntimeError(format_diagnostics(
+            name,
+            &diagnostics,
+        )));
+    }
+    for diagnostic in &diagnostics {
+        warn!("Plugin '{}': {}", name, diagnostic);
+    }
+
+    Ok(PluginInstance::new(
+        lua_plugin,
+        plugin_config.supported.actions,
+        locked_versions(&dependencies),
+    ))
+}
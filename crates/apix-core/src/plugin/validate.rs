@@ -0,0 +1,288 @@
+use mlua::{Lua, Value as LuaValue};
+use semver::Version;
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::plugin::{
+    args::{ArgSpec, validate_args},
+    config::PluginConfig,
+};
+
+const PLUGIN_SCHEMA: &str = include_str!("plugin.schema.json");
+
+/// Actions apix can invoke, each backed by a global function of the same name.
+pub const ACTIONS: &[&str] = &["create", "extend", "migrate", "info"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Position in a plugin source file, 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    pub(crate) fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub(crate) fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub(crate) fn at(mut self, file: &Path, line: usize, column: usize) -> Self {
+        self.location = Some(Location {
+            file: file.to_path_buf(),
+            line,
+            column,
+        });
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(
+                f,
+                "{}:{}:{}: ",
+                location.file.display(),
+                location.line,
+                location.column
+            )?;
+        }
+        match self.severity {
+            Severity::Error => write!(f, "error: {}", self.message),
+            Severity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+/// Formats diagnostics as an indented list under a headline.
+pub fn format_diagnostics(plugin: &str, diagnostics: &[Diagnostic]) -> String {
+    let mut out = format!("Plugin '{}' is invalid:", plugin);
+    for diagnostic in diagnostics {
+        out.push_str(&format!("\n  - {}", diagnostic));
+    }
+
+    out
+}
+
+/// Checks `plugin.toml` in `version_dir` (`<plugins>/<name>/<version>`) against
+/// the bundled schema and the folder it is installed in.
+pub fn validate_manifest(version_dir: &Path) -> Vec<Diagnostic> {
+    check_manifest(version_dir, true)
+}
+
+/// Checks `plugin.toml` of a working copy, whose folder is not named after
+/// the plugin and version yet.
+pub fn validate_working_copy(src_dir: &Path) -> Vec<Diagnostic> {
+    check_manifest(src_dir, false)
+}
+
+fn check_manifest(version_dir: &Path, check_folder: bool) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let manifest_path = version_dir.join("plugin.toml");
+
+    let value: serde_json::Value = match fs::read_to_string(&manifest_path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| toml::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(value) => value,
+        Err(e) => {
+            diagnostics.push(Diagnostic::error(format!(
+                "{} cannot be read: {}",
+                manifest_path.display(),
+                e
+            )));
+            return diagnostics;
+        }
+    };
+
+    let schema: serde_json::Value =
+        serde_json::from_str(PLUGIN_SCHEMA).expect("Bundled plugin schema is valid JSON");
+    let validator = jsonschema::validator_for(&schema).expect("Bundled plugin schema compiles");
+
+    for error in validator.iter_errors(&value) {
+        diagnostics.push(Diagnostic::error(format!(
+            "plugin.toml: {} (at '{}')",
+            error, error.instance_path
+        )));
+    }
+
+    let is_library = value.get("kind").and_then(|k| k.as_str()) == Some("library");
+    match value.get("supported") {
+        None if !is_library => diagnostics.push(Diagnostic::error(
+            "plugin.toml: [supported] is required unless kind = \"library\"",
+        )),
+        Some(supported)
+            if is_library
+                && supported
+                    .get("actions")
+                    .and_then(|a| a.as_array())
+                    .is_some_and(|a| !a.is_empty()) =>
+        {
+            diagnostics.push(Diagnostic::error(
+                "plugin.toml: libraries cannot declare supported.actions",
+            ))
+        }
+        _ => {}
+    }
+    if is_library
+        && value
+            .get("hooks")
+            .and_then(|h| h.as_object())
+            .is_some_and(|h| !h.is_empty())
+    {
+        diagnostics.push(Diagnostic::error(
+            "plugin.toml: libraries cannot declare hooks",
+        ));
+    }
+
+    if let Some(Ok(args)) = value
+        .get("extend")
+        .and_then(|extend| extend.get("args"))
+        .map(|args| serde_json::from_value::<Vec<ArgSpec>>(args.clone()))
+        && let Err(e) = validate_args(&args)
+    {
+        diagnostics.push(Diagnostic::error(format!("plugin.toml: extend: {}", e)));
+    }
+
+    if let Some(commands) = value.get("commands").and_then(|c| c.as_object()) {
+        if is_library && !commands.is_empty() {
+            diagnostics.push(Diagnostic::error(
+                "plugin.toml: libraries cannot declare commands",
+            ));
+        }
+        for (name, command) in commands {
+            if ACTIONS.contains(&name.as_str()) {
+                diagnostics.push(Diagnostic::error(format!(
+                    "plugin.toml: command '{}' has the name of a built-in action",
+                    name
+                )));
+            }
+            // Malformed arguments are already reported by the schema.
+            if let Some(Ok(args)) = command
+                .get("args")
+                .map(|args| serde_json::from_value::<Vec<ArgSpec>>(args.clone()))
+                && let Err(e) = validate_args(&args)
+            {
+                diagnostics.push(Diagnostic::error(format!(
+                    "plugin.toml: command '{}': {}",
+                    name, e
+                )));
+            }
+        }
+    }
+
+    let dir_name = |path: &Path| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let version_dir_name = dir_name(version_dir);
+    let plugin_dir_name = version_dir.parent().map(dir_name).unwrap_or_default();
+
+    if let Some(name) = value.get("name").and_then(|n| n.as_str())
+        && check_folder
+        && name != plugin_dir_name
+    {
+        diagnostics.push(Diagnostic::error(format!(
+            "plugin.toml name '{}' does not match its folder '{}'",
+            name, plugin_dir_name
+        )));
+    }
+
+    if let Some(version) = value.get("version").and_then(|v| v.as_str()) {
+        match Version::parse(version) {
+            Err(e) => diagnostics.push(Diagnostic::error(format!(
+                "plugin.toml version '{}' is not valid semver: {}",
+                version, e
+            ))),
+            Ok(parsed) if check_folder && parsed.to_string() != version_dir_name => diagnostics
+                .push(Diagnostic::error(format!(
+                    "plugin.toml version '{}' does not match its folder '{}'",
+                    version, version_dir_name
+                ))),
+            Ok(_) => {}
+        }
+    }
+
+    diagnostics
+}
+
+/// Checks that every declared action, hook and command has a global function after the
+/// plugin code ran, and that no action function is defined without being declared.
+pub fn validate_entry_points(lua: &Lua, config: &PluginConfig) -> Vec<Diagnostic> {
+    let globals = lua.globals();
+    let is_function =
+        |name: &str| matches!(globals.get::<LuaValue>(name), Ok(LuaValue::Function(_)));
+
+    let mut diagnostics = Vec::new();
+
+    for action in &config.supported.actions {
+        if ACTIONS.contains(&action.as_str()) && !is_function(action) {
+            diagnostics.push(Diagnostic::error(format!(
+                "action '{}' is declared in plugin.toml but no global function '{}' is defined",
+                action, action
+            )));
+        }
+    }
+
+    for (event, function) in &config.hooks {
+        if !is_function(function) {
+            diagnostics.push(Diagnostic::error(format!(
+                "hook '{}' is declared in plugin.toml but no global function '{}' is defined",
+                event, function
+            )));
+        }
+    }
+
+    for (name, command) in &config.commands {
+        let function = command.function_name(name);
+        if !is_function(&function) {
+            diagnostics.push(Diagnostic::error(format!(
+                "command '{}' is declared in plugin.toml but no global function '{}' is defined",
+                name, function
+            )));
+        }
+    }
+
+    for action in ACTIONS {
+        if is_function(action) && !config.supported.actions.iter().any(|a| a == action) {
+            diagnostics.push(Diagnostic::warning(format!(
+                "function '{}' is defined but '{}' is not listed in supported.actions, apix will not call it",
+                action, action
+            )));
+        }
+    }
+
+    diagnostics
+}
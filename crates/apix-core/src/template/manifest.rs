@@ -0,0 +1,200 @@
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs,
+    path::Path,
+};
+
+pub const MANIFEST_FILE: &str = "template.toml";
+
+/// Optional `template.toml` at the root of a template.
+#[derive(Debug, Default, Deserialize)]
+pub struct TemplateManifest {
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    #[serde(default)]
+    pub files: Vec<ConditionalFiles>,
+    #[serde(default)]
+    pub post_init: PostInit,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: VariableKind,
+    pub prompt: Option<String>,
+    pub default: Option<toml::Value>,
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableKind {
+    #[default]
+    String,
+    Bool,
+    Choice,
+}
+
+/// Files or folders (relative to the template root) that are only copied
+/// when `when` holds, e.g. `"use_docker"`, `"!use_docker"` or `"ci == github"`.
+#[derive(Debug, Deserialize)]
+pub struct ConditionalFiles {
+    pub path: String,
+    pub when: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PostInit {
+    /// Plugins added to `[plugins]` of monorepo.toml, name to version. They
+    /// are not installed.
+    #[serde(default)]
+    pub register_plugins: BTreeMap<String, String>,
+    #[serde(default)]
+    pub git_init: bool,
+    /// Message of the first commit. Requires `git_init`.
+    pub initial_commit: Option<String>,
+}
+
+impl TemplateManifest {
+    pub fn load(template_dir: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        let manifest_path = template_dir.join(MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&manifest_path)?;
+        let manifest: TemplateManifest =
+            toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+        manifest.validate()?;
+
+        Ok(Some(manifest))
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for variable in &self.variables {
+            if variable.kind == VariableKind::Choice && variable.choices.is_empty() {
+                return Err(
+                    format!("Variable '{}' of type choice has no choices", variable.name).into(),
+                );
+            }
+
+            if let Some(default) = variable.default_value() {
+                variable.normalize(&default)?;
+            }
+        }
+
+        if self.post_init.initial_commit.is_some() && !self.post_init.git_init {
+            return Err("post_init.initial_commit requires post_init.git_init".into());
+        }
+
+        Ok(())
+    }
+
+    /// Resolves every declared variable from `provided` (e.g. `--set` values),
+    /// falling back to `ask` for the rest. Built-in variables in `provided`
+    /// are passed through unchanged.
+    pub fn resolve_variables<F>(
+        &self,
+        provided: &HashMap<String, String>,
+        mut ask: F,
+    ) -> Result<HashMap<String, String>, Box<dyn Error>>
+    where
+        F: FnMut(&TemplateVariable) -> Result<String, Box<dyn Error>>,
+    {
+        let mut vars = provided.clone();
+
+        for variable in &self.variables {
+            let raw = match provided.get(&variable.name) {
+                Some(value) => value.clone(),
+                None => ask(variable)?,
+            };
+            vars.insert(variable.name.clone(), variable.normalize(&raw)?);
+        }
+
+        Ok(vars)
+    }
+
+    /// Whether the template file at `rel_path` should be copied.
+    pub fn includes(
+        &self,
+        rel_path: &Path,
+        vars: &HashMap<String, String>,
+    ) -> Result<bool, Box<dyn Error>> {
+        for rule in &self.files {
+            if rel_path.starts_with(&rule.path) && !condition_holds(&rule.when, vars)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl TemplateVariable {
+    pub fn default_value(&self) -> Option<String> {
+        self.default.as_ref().map(|value| match value {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+
+    pub fn prompt_text(&self) -> String {
+        let mut text = self.prompt.clone().unwrap_or_else(|| self.name.clone());
+
+        match self.kind {
+            VariableKind::Bool => text.push_str(" (yes/no)"),
+            VariableKind::Choice => text.push_str(&format!(" ({})", self.choices.join("/"))),
+            VariableKind::String => {}
+        }
+
+        text
+    }
+
+    /// Checks `raw` against the variable type. Booleans become `true`/`false`.
+    pub fn normalize(&self, raw: &str) -> Result<String, Box<dyn Error>> {
+        match self.kind {
+            VariableKind::String => Ok(raw.to_string()),
+            VariableKind::Bool => match raw.trim().to_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" => Ok("true".to_string()),
+                "false" | "no" | "n" | "0" => Ok("false".to_string()),
+                _ => Err(format!("'{}' expects yes or no, got '{}'", self.name, raw).into()),
+            },
+            VariableKind::Choice => {
+                if self.choices.iter().any(|c| c == raw) {
+                    Ok(raw.to_string())
+                } else {
+                    Err(format!(
+                        "'{}' must be one of {}, got '{}'",
+                        self.name,
+                        self.choices.join(", "),
+                        raw
+                    )
+                    .into())
+                }
+            }
+        }
+    }
+}
+
+fn condition_holds(when: &str, vars: &HashMap<String, String>) -> Result<bool, Box<dyn Error>> {
+    let lookup = |name: &str| {
+        vars.get(name.trim())
+            .ok_or_else(|| format!("Unknown variable '{}' in condition '{}'", name.trim(), when))
+    };
+
+    if let Some((name, value)) = when.split_once("!=") {
+        return Ok(lookup(name)? != value.trim());
+    }
+    if let Some((name, value)) = when.split_once("==") {
+        return Ok(lookup(name)? == value.trim());
+    }
+    if let Some(name) = when.trim().strip_prefix('!') {
+        return Ok(lookup(name)? != "true");
+    }
+
+    Ok(lookup(when)? == "true")
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fs, path::Path};
+
+const META_FILE: &str = "meta.yml";
+const META_SCHEMA: &str = include_str!("../../templates/meta.schema.json");
+const META_HEADER: &str = "# Structure of the monorepo templates\n\n";
+
+/// Contents of `~/.apix/templates/meta.yml`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateMeta {
+    pub templates: Vec<String>,
+    pub default: String,
+}
+
+impl TemplateMeta {
+    pub fn load(templates_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(templates_dir.join(META_FILE))
+            .map_err(|e| format!("Failed to read {}: {}", META_FILE, e))?;
+        let value: serde_json::Value = serde_yaml::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", META_FILE, e))?;
+
+        validate_against_schema(&value)?;
+
+        let meta: TemplateMeta = serde_json::from_value(value)?;
+        meta.validate()?;
+
+        Ok(meta)
+    }
+
+    /// Checks the schema and that the default template is listed.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        validate_against_schema(&serde_json::to_value(self)?)?;
+
+        if !self.templates.contains(&self.default) {
+            return Err(format!(
+                "Default template '{}' is not listed in {}",
+                self.default, META_FILE
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn save(&self, templates_dir: &Path) -> Result<(), Box<dyn Error>> {
+        self.validate()?;
+
+        let content = format!("{}{}", META_HEADER, serde_yaml::to_string(self)?);
+        fs::write(templates_dir.join(META_FILE), content)?;
+
+        Ok(())
+    }
+
+    /// Maps `"default"` to the configured default and checks the template is listed.
+    pub fn resolve(&self, name: &str) -> Result<String, Box<dyn Error>> {
+        let name = if name == "default" {
+            self.default.as_str()
+        } else {
+            name
+        };
+
+        if !self.templates.iter().any(|t| t == name) {
+            return Err(format!("Template '{}' not found", name).into());
+        }
+
+        Ok(name.to_string())
+    }
+}
+
+fn validate_against_schema(value: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+    let schema: serde_json::Value = serde_json::from_str(META_SCHEMA)?;
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| format!("Invalid bundled meta schema: {}", e))?;
+
+    let errors: Vec<String> = validator
+        .iter_errors(value)
+        .map(|e| format!("{} (at '{}')", e, e.instance_path))
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(format!("{} is invalid: {}", META_FILE, errors.join("; ")).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,4 @@
+pub mod manifest;
+pub mod meta;
+pub mod render;
+pub mod source;
@@ -0,0 +1,91 @@
+use regex::{Captures, Regex};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Component, Path},
+};
+
+use crate::template::manifest::{MANIFEST_FILE, TemplateManifest};
+
+/// Copies `src_dir` to `dst_dir`, skipping excluded files and replacing
+/// `{{var}}` placeholders in file contents and path names.
+pub fn render_template(
+    src_dir: &Path,
+    dst_dir: &Path,
+    manifest: Option<&TemplateManifest>,
+    vars: &HashMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    if !src_dir.is_dir() {
+        return Err(format!("Source directory {:?} does not exist", src_dir).into());
+    }
+
+    render_dir(src_dir, src_dir, dst_dir, manifest, vars)
+}
+
+fn render_dir(
+    root: &Path,
+    src_dir: &Path,
+    dst_dir: &Path,
+    manifest: Option<&TemplateManifest>,
+    vars: &HashMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dst_dir)?;
+
+    for entry in fs::read_dir(src_dir)? {
+        let entry_path = entry?.path();
+        let rel_path = entry_path.strip_prefix(root)?;
+
+        if rel_path == Path::new(MANIFEST_FILE) {
+            continue;
+        }
+
+        if let Some(manifest) = manifest
+            && !manifest.includes(rel_path, vars)?
+        {
+            continue;
+        }
+
+        let file_name = entry_path.file_name().unwrap().to_string_lossy();
+        let rendered_name = render_str(&file_name, vars);
+        // A variable must not move a file out of its folder.
+        if !matches!(
+            Path::new(&rendered_name).components().collect::<Vec<_>>()[..],
+            [Component::Normal(_)]
+        ) {
+            return Err(format!(
+                "Template path '{}' renders to '{}', which is not a plain file name",
+                rel_path.display(),
+                rendered_name
+            )
+            .into());
+        }
+        let dest_path = dst_dir.join(rendered_name);
+
+        if entry_path.is_dir() {
+            render_dir(root, &entry_path, &dest_path, manifest, vars)?;
+        } else {
+            match fs::read_to_string(&entry_path) {
+                Ok(content) => fs::write(&dest_path, render_str(&content, vars))?,
+                Err(_) => {
+                    fs::copy(&entry_path, &dest_path)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces `{{key}}` placeholders with their value in `vars` in a single
+/// pass, so values are never rendered again. Unknown keys are left as is.
+pub fn render_str(input: &str, vars: &HashMap<String, String>) -> String {
+    let placeholder = Regex::new(r"\{\{([A-Za-z0-9_-]+)\}\}").unwrap();
+
+    placeholder
+        .replace_all(input, |c: &Captures| match vars.get(&c[1]) {
+            Some(value) => value.clone(),
+            None => c[0].to_string(),
+        })
+        .into_owned()
+}
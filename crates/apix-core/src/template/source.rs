@@ -0,0 +1,209 @@
+use flate2::read::GzDecoder;
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+use crate::{template::meta::TemplateMeta, utils::fs::copy_dir_recursive};
+
+/// Where `apix init --template` takes its files from.
+#[derive(Debug)]
+pub enum TemplateSource {
+    /// A template registered in `meta.yml`, or `"default"`.
+    Installed(String),
+    Directory(PathBuf),
+    Archive(PathBuf),
+}
+
+impl TemplateSource {
+    /// Values containing a `/` (e.g. `./my-template`) or ending in an archive
+    /// extension are paths, anything else is the name of an installed
+    /// template, even if a folder of that name exists.
+    pub fn parse(value: &str) -> Self {
+        let is_path = value.contains('/')
+            || value.contains(std::path::MAIN_SEPARATOR)
+            || ARCHIVE_EXTENSIONS.iter().any(|ext| value.ends_with(ext));
+
+        if !is_path {
+            return TemplateSource::Installed(value.to_string());
+        }
+
+        Self::from_path(Path::new(value))
+    }
+
+    /// Directories are directory sources, anything else an archive.
+    pub fn from_path(path: &Path) -> Self {
+        if path.is_dir() {
+            TemplateSource::Directory(path.to_path_buf())
+        } else {
+            TemplateSource::Archive(path.to_path_buf())
+        }
+    }
+}
+
+const ARCHIVE_EXTENSIONS: &[&str] = &[".tar.gz", ".tgz", ".tar"];
+
+/// A template resolved to a directory on disk. Archives are unpacked into a
+/// temporary folder that lives as long as this value.
+pub struct ResolvedTemplate {
+    pub name: String,
+    pub dir: PathBuf,
+    _unpacked: Option<TempDir>,
+}
+
+pub fn resolve_template(
+    templates_dir: &Path,
+    source: TemplateSource,
+) -> Result<ResolvedTemplate, Box<dyn Error>> {
+    match source {
+        TemplateSource::Installed(name) => {
+            let name = TemplateMeta::load(templates_dir)?.resolve(&name)?;
+            let dir = templates_dir.join(&name);
+
+            if !dir.is_dir() {
+                return Err(format!(
+                    "Selected template '{}' folder not found in templates directory",
+                    name
+                )
+                .into());
+            }
+
+            Ok(ResolvedTemplate {
+                name,
+                dir,
+                _unpacked: None,
+            })
+        }
+        TemplateSource::Directory(dir) => Ok(ResolvedTemplate {
+            name: source_name(&dir)?,
+            dir,
+            _unpacked: None,
+        }),
+        TemplateSource::Archive(archive) => {
+            if !archive.is_file() {
+                return Err(
+                    format!("Template source '{}' does not exist", archive.display()).into(),
+                );
+            }
+            let unpacked = tempfile::tempdir()?;
+            let dir = extract_archive(&archive, unpacked.path())?;
+
+            Ok(ResolvedTemplate {
+                name: source_name(&archive)?,
+                dir,
+                _unpacked: Some(unpacked),
+            })
+        }
+    }
+}
+
+/// Unpacks a `.tar`, `.tar.gz` or `.tgz` archive and returns the template root.
+/// Archives wrapping everything in a single top-level folder are unwrapped.
+pub fn extract_archive(archive: &Path, dst: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let file_name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let file = fs::File::open(archive)?;
+
+    if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        tar::Archive::new(GzDecoder::new(file)).unpack(dst)?;
+    } else if file_name.ends_with(".tar") {
+        tar::Archive::new(file).unpack(dst)?;
+    } else {
+        return Err(format!(
+            "Unsupported template archive '{}', expected .tar, .tar.gz or .tgz",
+            archive.display()
+        )
+        .into());
+    }
+
+    let entries: Vec<PathBuf> = fs::read_dir(dst)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+
+    match entries.as_slice() {
+        [single] if single.is_dir() => Ok(single.clone()),
+        _ => Ok(dst.to_path_buf()),
+    }
+}
+
+/// Copies a template directory or archive into the templates directory and
+/// registers it in `meta.yml`. Returns the registered name.
+pub fn add_template(
+    templates_dir: &Path,
+    source: &Path,
+    name: Option<&str>,
+    make_default: bool,
+) -> Result<String, Box<dyn Error>> {
+    let resolved = resolve_template(templates_dir, TemplateSource::from_path(source))?;
+    let name = name.map(str::to_string).unwrap_or(resolved.name.clone());
+
+    let mut meta = TemplateMeta::load(templates_dir)?;
+    if meta.templates.contains(&name) {
+        return Err(format!("Template '{}' already exists", name).into());
+    }
+
+    meta.templates.push(name.clone());
+    if make_default {
+        meta.default = name.clone();
+    }
+    meta.validate()?;
+
+    let dst = templates_dir.join(&name);
+    if dst.exists() {
+        fs::remove_dir_all(&dst)?;
+    }
+
+    if let Err(e) = copy_dir_recursive(&resolved.dir, &dst)
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|_| meta.save(templates_dir))
+    {
+        let _ = fs::remove_dir_all(&dst);
+        return Err(e);
+    }
+
+    Ok(name)
+}
+
+pub fn remove_template(templates_dir: &Path, name: &str) -> Result<(), Box<dyn Error>> {
+    let mut meta = TemplateMeta::load(templates_dir)?;
+
+    if !meta.templates.iter().any(|t| t == name) {
+        return Err(format!("Template '{}' not found", name).into());
+    }
+
+    if meta.default == name {
+        return Err(format!(
+            "Template '{}' is the default, run 'apix template default <name>' with another template first",
+            name
+        )
+        .into());
+    }
+
+    meta.templates.retain(|t| t != name);
+    meta.save(templates_dir)?;
+
+    let dir = templates_dir.join(name);
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+
+    Ok(())
+}
+
+fn source_name(path: &Path) -> Result<String, Box<dyn Error>> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Cannot derive a template name from '{}'", path.display()))?;
+
+    let name = ARCHIVE_EXTENSIONS
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(ext))
+        .unwrap_or(&file_name);
+
+    Ok(name.to_string())
+}
@@ -1,6 +1,9 @@
-use std::{env, fs, path::PathBuf};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
-use crate::utils::fs::ensure_dir_and_copy_files;
+use crate::{template::meta::TemplateMeta, utils::fs::ensure_dir_and_copy_files};
 
 #[derive(Debug)]
 pub struct InternalDir {
@@ -26,8 +29,7 @@ impl InternalDir {
         let templates_src = crate_root.join("templates");
         let migrations_src = crate_root.join("migrations");
 
-        ensure_dir_and_copy_files(&templates_dir, &templates_src)
-            .expect("Failed to copy templates");
+        seed_templates(&templates_dir, &templates_src).expect("Failed to copy templates");
         ensure_dir_and_copy_files(&migrations_dir, &migrations_src)
             .expect("Failed to copy migrations");
 
@@ -96,3 +98,39 @@ impl InternalDir {
         &self.plugins_dir
     }
 }
+
+/// Copies each bundled template whose folder is missing or empty. Templates
+/// dropped from `meta.yml` by 'apix template remove' are not seeded again.
+fn seed_templates(
+    templates_dir: &Path,
+    templates_src: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listed = TemplateMeta::load(templates_dir)
+        .ok()
+        .map(|meta| meta.templates);
+    fs::create_dir_all(templates_dir)?;
+
+    for entry in fs::read_dir(templates_src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dest_path = templates_dir.join(entry.file_name());
+
+        if !src_path.is_dir() {
+            if !dest_path.exists() {
+                fs::copy(src_path, dest_path)?;
+            }
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        let removed = listed
+            .as_ref()
+            .is_some_and(|listed| !listed.contains(&name));
+        let empty = fs::read_dir(&dest_path).map_or(true, |mut entries| entries.next().is_none());
+        if !removed && empty {
+            ensure_dir_and_copy_files(&dest_path, &src_path)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,12 +1,19 @@
 function create(project_name)
-  ctx:log("Creating " .. project_name)
+  ctx.info("Creating " .. project_name)
 
-  ctx:create_file(project_name .. "/Cargo.toml", [[
+  ctx.create_file(project_name .. "/Cargo.toml", [[
     [package]
     name = "]] .. project_name .. [["
     version = "0.1.0"
   ]])
 
+  ctx.declare_created({
+    name = project_name,
+    kind = "project",
+    path = project_name,
+    language = "rust",
+  })
+
   return 0
 end
 
@@ -18,7 +25,7 @@ function migrate(from_version)
   return 0
 end
 
-function help()
+function info()
     return {
         usage = {
             "apix plugin example-plugin create <name>",